@@ -0,0 +1,272 @@
+use crate::diagnostic::{ColorChoice, ErrorFormat};
+use std::path::PathBuf;
+use std::str::FromStr;
+use thiserror::Error;
+
+pub const USAGE: &str = "\
+Usage: maga [options] <file>...
+       maga run [options] <file> [args]...
+
+`maga run -` reads the program from stdin. `maga run` exits with the exit code of the program,
+or with 125 when the program could not be compiled.
+
+Options:
+    -o <path>         Write output to <path>, `-` writes to stdout
+    --emit=<kind>     Output kind: tokens, ast, doc, llvm-ir (default), bitcode, asm, obj,
+                      exe
+    --target=<triple> Generate code for <triple> instead of the host
+    --color=<when>    Color diagnostics: auto (default), always, never
+    --error-format=<format>
+                      Diagnostic format: human (default), json
+    -h, --help        Print this help message
+    -V, --version     Print the compiler version";
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("No input files")]
+    MissingInput,
+
+    #[error("Missing value for argument {0}")]
+    MissingValue(String),
+
+    #[error("Unknown argument: {0}")]
+    UnknownArgument(String),
+
+    #[error(
+        "Invalid emit kind: {0}, expected one of tokens, ast, doc, llvm-ir, bitcode, asm, obj, exe"
+    )]
+    InvalidEmit(String),
+
+    #[error("Invalid color choice: {0}, expected one of auto, always, never")]
+    InvalidColor(String),
+
+    #[error("Invalid error format: {0}, expected one of human, json")]
+    InvalidErrorFormat(String),
+
+    #[error("-o cannot be used with multiple input files when emitting {0}")]
+    OutputWithMultipleInputs(Emit),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Emit {
+    Tokens,
+    Ast,
+    /// Signatures and doc comments of the functions
+    Doc,
+    LlvmIr,
+    Bitcode,
+    Asm,
+    Obj,
+    Exe,
+}
+
+impl Emit {
+    /// File extension used when no output path is given, `None` means the output goes to stdout
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Emit::Tokens | Emit::Ast | Emit::Doc => None,
+            Emit::LlvmIr => Some("ll"),
+            Emit::Bitcode => Some("bc"),
+            Emit::Asm => Some("s"),
+            Emit::Obj => Some("o"),
+            Emit::Exe => Some(""),
+        }
+    }
+}
+
+impl FromStr for Emit {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tokens" => Ok(Emit::Tokens),
+            "ast" => Ok(Emit::Ast),
+            "doc" => Ok(Emit::Doc),
+            "llvm-ir" => Ok(Emit::LlvmIr),
+            "bitcode" => Ok(Emit::Bitcode),
+            "asm" => Ok(Emit::Asm),
+            "obj" => Ok(Emit::Obj),
+            "exe" => Ok(Emit::Exe),
+            kind => Err(CliError::InvalidEmit(kind.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for Emit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Emit::Tokens => "tokens",
+            Emit::Ast => "ast",
+            Emit::Doc => "doc",
+            Emit::LlvmIr => "llvm-ir",
+            Emit::Bitcode => "bitcode",
+            Emit::Asm => "asm",
+            Emit::Obj => "obj",
+            Emit::Exe => "exe",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub inputs: Vec<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub emit: Emit,
+    pub target: Option<String>,
+    pub diagnostics: DiagnosticOptions,
+}
+
+#[derive(Debug)]
+pub struct RunOptions {
+    pub input: PathBuf,
+    /// Arguments passed on to the program, not including the program name
+    pub args: Vec<String>,
+    pub diagnostics: DiagnosticOptions,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DiagnosticOptions {
+    pub color: ColorChoice,
+    pub error_format: ErrorFormat,
+}
+
+impl DiagnosticOptions {
+    /// Applies `arg` if it is a diagnostic option, returns whether it was one
+    fn parse_arg(
+        &mut self,
+        arg: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<bool, CliError> {
+        if let Some(color) = option_value(arg, "--color", args)? {
+            self.color = match color.as_str() {
+                "auto" => ColorChoice::Auto,
+                "always" => ColorChoice::Always,
+                "never" => ColorChoice::Never,
+                _ => return Err(CliError::InvalidColor(color)),
+            };
+        } else if let Some(format) = option_value(arg, "--error-format", args)? {
+            self.error_format = match format.as_str() {
+                "human" => ErrorFormat::Human,
+                "json" => ErrorFormat::Json,
+                _ => return Err(CliError::InvalidErrorFormat(format)),
+            };
+        } else {
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Compile(Options),
+    Run(RunOptions),
+    Help,
+    Version,
+}
+
+impl Command {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut args = args.into_iter().peekable();
+
+        if args.peek().map(String::as_str) == Some("run") {
+            args.next();
+            return Self::parse_run(args);
+        }
+
+        let mut inputs = Vec::new();
+        let mut output = None;
+        let mut emit = Emit::LlvmIr;
+        let mut target = None;
+        let mut diagnostics = DiagnosticOptions::default();
+
+        while let Some(arg) = args.next() {
+            if diagnostics.parse_arg(&arg, &mut args)? {
+                continue;
+            }
+
+            if let Some(kind) = option_value(&arg, "--emit", &mut args)? {
+                emit = kind.parse()?;
+            } else if let Some(triple) = option_value(&arg, "--target", &mut args)? {
+                target = Some(triple);
+            } else {
+                match arg.as_str() {
+                    "-h" | "--help" => return Ok(Command::Help),
+                    "-V" | "--version" => return Ok(Command::Version),
+                    "-o" => {
+                        let path = args.next().ok_or(CliError::MissingValue(arg))?;
+                        output = Some(PathBuf::from(path));
+                    }
+                    _ if arg.starts_with('-') => return Err(CliError::UnknownArgument(arg)),
+                    _ => inputs.push(PathBuf::from(arg)),
+                }
+            }
+        }
+
+        if inputs.is_empty() {
+            return Err(CliError::MissingInput);
+        }
+
+        //All inputs are linked into a single executable, every other output is produced per input
+        if output.is_some() && inputs.len() > 1 && emit != Emit::Exe {
+            return Err(CliError::OutputWithMultipleInputs(emit));
+        }
+
+        Ok(Command::Compile(Options {
+            inputs,
+            output,
+            emit,
+            target,
+            diagnostics,
+        }))
+    }
+
+    /// Parses `maga run [options] <file> [args]...`, everything after the file is passed to the program.
+    /// A file of `-` reads the program from stdin.
+    fn parse_run(mut args: impl Iterator<Item = String>) -> Result<Self, CliError> {
+        let mut diagnostics = DiagnosticOptions::default();
+
+        while let Some(arg) = args.next() {
+            if diagnostics.parse_arg(&arg, &mut args)? {
+                continue;
+            }
+
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(Command::Help),
+                _ if arg.starts_with('-') && arg != "-" => {
+                    return Err(CliError::UnknownArgument(arg))
+                }
+                _ => {
+                    return Ok(Command::Run(RunOptions {
+                        input: PathBuf::from(arg),
+                        args: args.collect(),
+                        diagnostics,
+                    }))
+                }
+            }
+        }
+
+        Err(CliError::MissingInput)
+    }
+}
+
+/// Returns the value of `arg` if it is either `<name>=<value>` or `<name>` followed by a value
+fn option_value(
+    arg: &str,
+    name: &str,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<Option<String>, CliError> {
+    if arg == name {
+        return args
+            .next()
+            .map(Some)
+            .ok_or_else(|| CliError::MissingValue(arg.to_owned()));
+    }
+
+    Ok(arg
+        .strip_prefix(name)
+        .and_then(|value| value.strip_prefix('='))
+        .map(str::to_owned))
+}
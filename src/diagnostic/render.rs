@@ -0,0 +1,294 @@
+use crate::diagnostic::{Diagnostic, Label, Severity};
+use crate::span::LineIndex;
+use std::fmt::Write as _;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    #[default]
+    Human,
+    /// One JSON object per line, meant for editors and other tools
+    Json,
+}
+
+/// The file a diagnostic refers to
+#[derive(Clone, Copy)]
+pub struct Source<'a> {
+    pub path: &'a Path,
+    pub text: &'a str,
+}
+
+pub struct Emitter {
+    color: bool,
+    format: ErrorFormat,
+}
+
+impl Emitter {
+    pub fn new(color: ColorChoice, format: ErrorFormat) -> Self {
+        let color = match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none() && io::stderr().is_terminal()
+            }
+        };
+
+        Self { color, format }
+    }
+
+    /// Writes `diagnostic` to stderr
+    pub fn emit(&self, diagnostic: &Diagnostic, source: Option<Source>) {
+        let _ = io::stderr().write_all(self.render(diagnostic, source).as_bytes());
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic, source: Option<Source>) -> String {
+        match self.format {
+            ErrorFormat::Human => self.render_human(diagnostic, source),
+            ErrorFormat::Json => render_json(diagnostic, source),
+        }
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_owned()
+        }
+    }
+
+    fn render_human(&self, diagnostic: &Diagnostic, source: Option<Source>) -> String {
+        let severity_color = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        let mut output = format!(
+            "{}{}\n",
+            self.paint(severity_color, &diagnostic.severity.to_string()),
+            self.paint(BOLD, &format!(": {}", diagnostic.message)),
+        );
+
+        let Some(source) = source else {
+            for note in &diagnostic.notes {
+                let _ = writeln!(output, "{}: {}", self.paint(BOLD, "note"), note);
+            }
+            for help in &diagnostic.help {
+                let _ = writeln!(output, "{}: {}", self.paint(CYAN, "help"), help);
+            }
+            return output;
+        };
+
+        let index = LineIndex::new(source.text);
+
+        let mut labels = diagnostic.labels.iter().collect::<Vec<_>>();
+        labels.sort_by_key(|label| (label.span.start, !label.primary));
+
+        let gutter = labels
+            .iter()
+            .map(|label| index.line_column(label.span.start).line)
+            .max()
+            .unwrap_or(1)
+            .to_string()
+            .len();
+        let blank = " ".repeat(gutter);
+
+        match diagnostic.primary_label() {
+            Some(label) => {
+                let _ = writeln!(
+                    output,
+                    "{}{} {}:{}",
+                    blank,
+                    self.paint(BLUE, "-->"),
+                    source.path.display(),
+                    index.line_column(label.span.start)
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    output,
+                    "{}{} {}",
+                    blank,
+                    self.paint(BLUE, "-->"),
+                    source.path.display()
+                );
+            }
+        }
+
+        if !labels.is_empty() {
+            let _ = writeln!(output, "{} {}", blank, self.paint(BLUE, "|"));
+        }
+
+        let mut previous_line = None;
+        for label in labels {
+            let start = index.line_column(label.span.start);
+            let end = index.line_column(label.span.end);
+            let line = index.line(start.line);
+
+            //Spans covering several lines are underlined to the end of their first line
+            let end_column = if end.line == start.line {
+                end.column
+            } else {
+                line.chars().count() + 1
+            };
+
+            if previous_line != Some(start.line) {
+                let _ = writeln!(
+                    output,
+                    "{} {} {}",
+                    self.paint(BLUE, &format!("{:>width$}", start.line, width = gutter)),
+                    self.paint(BLUE, "|"),
+                    line
+                );
+                previous_line = Some(start.line);
+            }
+
+            let _ = writeln!(
+                output,
+                "{} {} {}",
+                blank,
+                self.paint(BLUE, "|"),
+                self.render_underline(label, start.column, end_column, severity_color)
+            );
+        }
+
+        for note in &diagnostic.notes {
+            let _ = writeln!(
+                output,
+                "{} {} {}: {}",
+                blank,
+                self.paint(BLUE, "="),
+                self.paint(BOLD, "note"),
+                note
+            );
+        }
+        for help in &diagnostic.help {
+            let _ = writeln!(
+                output,
+                "{} {} {}: {}",
+                blank,
+                self.paint(BLUE, "="),
+                self.paint(CYAN, "help"),
+                help
+            );
+        }
+
+        output
+    }
+
+    fn render_underline(
+        &self,
+        label: &Label,
+        column: usize,
+        end_column: usize,
+        color: &str,
+    ) -> String {
+        let (marker, color) = if label.primary {
+            ("^", color)
+        } else {
+            ("-", BLUE)
+        };
+
+        let mut underline = marker.repeat(end_column.saturating_sub(column).max(1));
+        if !label.message.is_empty() {
+            underline.push(' ');
+            underline.push_str(&label.message);
+        }
+
+        format!(
+            "{}{}",
+            " ".repeat(column - 1),
+            self.paint(color, &underline)
+        )
+    }
+}
+
+fn render_json(diagnostic: &Diagnostic, source: Option<Source>) -> String {
+    let index = source.map(|source| LineIndex::new(source.text));
+
+    let labels = diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            let mut json = format!(
+                "{{\"message\":{},\"primary\":{},\"start\":{},\"end\":{}",
+                json_string(&label.message),
+                label.primary,
+                label.span.start,
+                label.span.end
+            );
+
+            if let Some(index) = &index {
+                let start = index.line_column(label.span.start);
+                let end = index.line_column(label.span.end);
+                let _ = write!(
+                    json,
+                    ",\"line\":{},\"column\":{},\"end_line\":{},\"end_column\":{}",
+                    start.line, start.column, end.line, end.column
+                );
+            }
+
+            json.push('}');
+            json
+        })
+        .collect::<Vec<_>>();
+
+    let file = source
+        .map(|source| json_string(&source.path.to_string_lossy()))
+        .unwrap_or_else(|| "null".to_owned());
+
+    format!(
+        "{{\"severity\":{},\"message\":{},\"file\":{},\"labels\":[{}],\"notes\":[{}],\"help\":[{}]}}\n",
+        json_string(&diagnostic.severity.to_string()),
+        json_string(&diagnostic.message),
+        file,
+        labels.join(","),
+        json_strings(&diagnostic.notes),
+        json_strings(&diagnostic.help),
+    )
+}
+
+fn json_strings(strings: &[String]) -> String {
+    strings
+        .iter()
+        .map(|string| json_string(string))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn json_string(string: &str) -> String {
+    let mut json = String::with_capacity(string.len() + 2);
+    json.push('"');
+
+    for char in string.chars() {
+        match char {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            char if (char as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", char as u32);
+            }
+            char => json.push(char),
+        }
+    }
+
+    json.push('"');
+    json
+}
@@ -0,0 +1,339 @@
+use crate::cli::{Emit, Options, RunOptions};
+use crate::diagnostic::{Diagnostic, Emitter, Source};
+use crate::generation::{
+    run_main, CodeGenContext, CodeGenError, Module, OutputKind, TargetMachine,
+};
+use crate::lexer::Lexer;
+use crate::parser::{Element, Parser, SourceFile};
+use crate::sema::{self, hir::Program};
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DriverError {
+    #[error("Failed to read {0}: {1}")]
+    Read(PathBuf, io::Error),
+
+    #[error("Failed to write {0}: {1}")]
+    Write(PathBuf, io::Error),
+
+    #[error("Aborting due to previous errors")]
+    Aborted,
+
+    #[error(transparent)]
+    CodeGen(#[from] CodeGenError),
+
+    #[error("Failed to run linker {0}: {1}")]
+    LinkerNotFound(String, io::Error),
+
+    #[error("Linking failed with {0}")]
+    LinkFailed(process::ExitStatus),
+
+    #[error("Cannot write an executable to stdout")]
+    ExecutableToStdout,
+}
+
+pub fn compile(options: &Options, emitter: &Emitter) -> Result<(), DriverError> {
+    let target_machine = match options.emit {
+        Emit::Asm | Emit::Obj | Emit::Exe => Some(TargetMachine::new(options.target.as_deref())?),
+        _ => None,
+    };
+
+    let mut objects = Vec::new();
+
+    for (index, input) in options.inputs.iter().enumerate() {
+        match compile_file(input, index, options, target_machine.as_ref(), emitter) {
+            Ok(object) => objects.extend(object),
+            Err(err) => {
+                remove_objects(&objects);
+                return Err(err);
+            }
+        }
+    }
+
+    if options.emit != Emit::Exe {
+        return Ok(());
+    }
+
+    let result = output_path(&options.inputs[0], options)
+        .ok_or(DriverError::ExecutableToStdout)
+        .and_then(|output| link(&objects, &output));
+    remove_objects(&objects);
+
+    result
+}
+
+/// Compiles `options.input` in memory and runs its `main` function, returns the exit code of the program.
+/// An input of `-` reads the program from stdin.
+pub fn run(options: &RunOptions, emitter: &Emitter) -> Result<i32, DriverError> {
+    let input = options.input.as_path();
+
+    let source = if input.as_os_str() == "-" {
+        io::read_to_string(io::stdin()).map_err(|err| DriverError::Read(input.to_owned(), err))?
+    } else {
+        fs::read_to_string(input).map_err(|err| DriverError::Read(input.to_owned(), err))?
+    };
+
+    let mut tokens = Lexer::new(&source).tokens();
+    let source_file = Parser::new(&mut tokens)
+        .parse()
+        .map_err(|diagnostics| report(emitter, input, &source, diagnostics))?;
+
+    let program = analyze(emitter, input, &source, &source_file)?;
+
+    let mut context = CodeGenContext::new()?;
+    let mut module = context.context_mut().create_module(&module_name(input))?;
+    context.generate(&mut module, &program)?;
+
+    let mut args = vec![input.to_string_lossy().into_owned()];
+    args.extend(options.args.iter().cloned());
+
+    run_main(&context, module, &source_file, &args)
+        .map_err(|err| report(emitter, input, &source, [Diagnostic::from(&err)]))
+}
+
+/// Compiles a single source file, returns the path of the temporary object file when emitting an executable.
+/// `index` is the position of the file among the inputs.
+fn compile_file(
+    input: &Path,
+    index: usize,
+    options: &Options,
+    target_machine: Option<&TargetMachine>,
+    emitter: &Emitter,
+) -> Result<Option<PathBuf>, DriverError> {
+    let source =
+        fs::read_to_string(input).map_err(|err| DriverError::Read(input.to_owned(), err))?;
+
+    let mut tokens = Lexer::new(&source).tokens();
+
+    if options.emit == Emit::Tokens {
+        check(emitter, input, &source, tokens.take_diagnostics())?;
+
+        let mut output = String::new();
+        for token in tokens.tokens() {
+            let _ = writeln!(output, "{} {:?}", token.span, token.token);
+        }
+        write_output(input, options, output.as_bytes())?;
+        return Ok(None);
+    }
+
+    let source_file = Parser::new(&mut tokens)
+        .parse()
+        .map_err(|diagnostics| report(emitter, input, &source, diagnostics))?;
+
+    if options.emit == Emit::Ast {
+        let output = format!("{:#?}\n", source_file);
+        write_output(input, options, output.as_bytes())?;
+        return Ok(None);
+    }
+
+    if options.emit == Emit::Doc {
+        write_output(input, options, document(&source, &source_file).as_bytes())?;
+        return Ok(None);
+    }
+
+    let program = analyze(emitter, input, &source, &source_file)?;
+
+    let mut context = CodeGenContext::new()?;
+    let mut module = context.context_mut().create_module(&module_name(input))?;
+
+    if let Some(target_machine) = target_machine {
+        target_machine.configure_module(&mut module);
+    }
+
+    context.generate(&mut module, &program)?;
+
+    match (options.emit, target_machine) {
+        (Emit::LlvmIr, _) => {
+            write_output(input, options, module.print_to_string().as_bytes())?;
+        }
+        (Emit::Bitcode, _) => {
+            write_output(input, options, &module.write_bitcode_to_memory()?)?;
+        }
+        (Emit::Asm, Some(target_machine)) => {
+            emit_native(
+                input,
+                options,
+                target_machine,
+                &module,
+                OutputKind::Assembly,
+            )?;
+        }
+        (Emit::Obj, Some(target_machine)) => {
+            emit_native(input, options, target_machine, &module, OutputKind::Object)?;
+        }
+        (Emit::Exe, Some(target_machine)) => {
+            //Inputs in different directories can share a file name, the index keeps their objects apart
+            let object = env::temp_dir().join(format!(
+                "{}-{}-{}.o",
+                module_name(input),
+                process::id(),
+                index
+            ));
+            target_machine.emit_to_file(&module, &object, OutputKind::Object)?;
+            return Ok(Some(object));
+        }
+        _ => unreachable!("native output requires a target machine"),
+    }
+
+    Ok(None)
+}
+
+/// Lists the signature of every function followed by its indented doc comment
+fn document(source: &str, source_file: &SourceFile) -> String {
+    let mut output = String::new();
+
+    for Element::Function(function) in source_file.iter() {
+        let end = function
+            .body
+            .as_ref()
+            .map_or(function.span.end, |body| body.span.start);
+        //An extern declaration ends with `;` instead of a body
+        let signature = source[function.span.start..end].trim_end();
+        let _ = writeln!(output, "{}", signature.trim_end_matches(';'));
+
+        for line in &function.doc {
+            if line.is_empty() {
+                output.push('\n');
+            } else {
+                let _ = writeln!(output, "    {}", line);
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+fn emit_native(
+    input: &Path,
+    options: &Options,
+    target_machine: &TargetMachine,
+    module: &Module,
+    kind: OutputKind,
+) -> Result<(), DriverError> {
+    match output_path(input, options) {
+        Some(path) => Ok(target_machine.emit_to_file(module, &path, kind)?),
+        None => write_output(
+            input,
+            options,
+            &target_machine.emit_to_memory(module, kind)?,
+        ),
+    }
+}
+
+/// Links object files into an executable using the system C compiler, `$CC` overrides the default `cc`
+fn link(objects: &[PathBuf], output: &Path) -> Result<(), DriverError> {
+    let linker = env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+
+    let status = process::Command::new(&linker)
+        .args(objects)
+        .arg("-o")
+        .arg(output)
+        .status()
+        .map_err(|err| DriverError::LinkerNotFound(linker, err))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(DriverError::LinkFailed(status))
+    }
+}
+
+fn remove_objects(objects: &[PathBuf]) {
+    for object in objects {
+        let _ = fs::remove_file(object);
+    }
+}
+
+/// The module is named after the file it was compiled from, e.g. `files/example.maga` becomes `example`
+fn module_name(input: &Path) -> String {
+    input
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "main".to_owned())
+}
+
+/// Resolves where the output for `input` goes, `None` means stdout
+fn output_path(input: &Path, options: &Options) -> Option<PathBuf> {
+    match &options.output {
+        Some(path) if path.as_os_str() == "-" => None,
+        Some(path) => Some(path.clone()),
+        None => options
+            .emit
+            .extension()
+            .map(|extension| PathBuf::from(module_name(input)).with_extension(extension)),
+    }
+}
+
+fn write_output(input: &Path, options: &Options, bytes: &[u8]) -> Result<(), DriverError> {
+    match output_path(input, options) {
+        Some(path) => fs::write(&path, bytes).map_err(|err| DriverError::Write(path, err)),
+        None => io::stdout()
+            .write_all(bytes)
+            .map_err(|err| DriverError::Write(PathBuf::from("-"), err)),
+    }
+}
+
+/// Emits `diagnostics` for `input` and returns the error aborting the compilation
+fn report(
+    emitter: &Emitter,
+    input: &Path,
+    source: &str,
+    diagnostics: impl IntoIterator<Item = Diagnostic>,
+) -> DriverError {
+    let source = Source {
+        path: input,
+        text: source,
+    };
+
+    for diagnostic in diagnostics {
+        emitter.emit(&diagnostic, Some(source));
+    }
+
+    DriverError::Aborted
+}
+
+/// Runs semantic analysis on `source_file`, its warnings are emitted right away
+fn analyze(
+    emitter: &Emitter,
+    input: &Path,
+    source: &str,
+    source_file: &SourceFile,
+) -> Result<Program, DriverError> {
+    let (program, warnings) = sema::analyze(source_file)
+        .map_err(|diagnostics| report(emitter, input, source, diagnostics))?;
+    check(emitter, input, source, warnings)?;
+
+    Ok(program)
+}
+
+/// Emits `diagnostics` for `input`, aborts the compilation if any of them is an error
+fn check(
+    emitter: &Emitter,
+    input: &Path,
+    source: &str,
+    diagnostics: impl IntoIterator<Item = Diagnostic>,
+) -> Result<(), DriverError> {
+    let source = Source {
+        path: input,
+        text: source,
+    };
+
+    let mut failed = false;
+    for diagnostic in diagnostics {
+        failed |= diagnostic.is_error();
+        emitter.emit(&diagnostic, Some(source));
+    }
+
+    if failed {
+        Err(DriverError::Aborted)
+    } else {
+        Ok(())
+    }
+}
@@ -1,57 +1,185 @@
-use crate::generation::expressions::{CodeGenExpr, ExpressionCodeGen, ExpressionCodeGenParams};
+use crate::generation::expressions::{ExpressionCodeGen, ExpressionCodeGenParams};
 use crate::generation::CodeGenError;
-use crate::parser::{BinaryExpression, Operator};
-use llvm_sys::core::{LLVMBuildAdd, LLVMBuildICmp, LLVMBuildMul, LLVMBuildSub};
-use llvm_sys::LLVMIntPredicate;
+use crate::parser::Operator;
+use crate::sema::hir::Binary;
+use crate::sema::Type;
+use llvm_sys::core::{
+    LLVMAddIncoming, LLVMAppendBasicBlockInContext, LLVMBuildAShr, LLVMBuildAdd, LLVMBuildAnd,
+    LLVMBuildBr, LLVMBuildCondBr, LLVMBuildFAdd, LLVMBuildFCmp, LLVMBuildFDiv, LLVMBuildFMul,
+    LLVMBuildFRem, LLVMBuildFSub, LLVMBuildICmp, LLVMBuildLShr, LLVMBuildMul, LLVMBuildOr,
+    LLVMBuildPhi, LLVMBuildSDiv, LLVMBuildSRem, LLVMBuildShl, LLVMBuildSub, LLVMBuildUDiv,
+    LLVMBuildURem, LLVMBuildXor, LLVMConstInt, LLVMGetBasicBlockParent, LLVMGetInsertBlock,
+    LLVMPositionBuilderAtEnd,
+};
+use llvm_sys::prelude::LLVMValueRef;
+use llvm_sys::{LLVMIntPredicate, LLVMRealPredicate};
+
+impl ExpressionCodeGen<'_> for Binary {
+    fn generate(&self, params: &mut ExpressionCodeGenParams) -> Result<LLVMValueRef, CodeGenError> {
+        if matches!(self.operator, Operator::LogicalAnd | Operator::LogicalOr) {
+            return self.generate_logical(params);
+        }
 
-impl ExpressionCodeGen<'_> for BinaryExpression {
-    fn generate(&self, params: &ExpressionCodeGenParams) -> Result<CodeGenExpr, CodeGenError> {
         let lhs = self.lhs.generate(params)?;
         let rhs = self.rhs.generate(params)?;
 
-        if lhs.r#type != rhs.r#type {
-            return Err(CodeGenError::TypesNotEqual(lhs.r#type, rhs.r#type));
+        Ok(generate_operation(
+            params,
+            self.operator,
+            &self.lhs.r#type,
+            lhs,
+            rhs,
+        ))
+    }
+}
+
+/// Applies an arithmetic, bitwise or comparison operator to two values of type `r#type`, this is shared with
+/// compound assignments
+pub fn generate_operation(
+    params: &mut ExpressionCodeGenParams,
+    operator: Operator,
+    r#type: &Type,
+    lhs: LLVMValueRef,
+    rhs: LLVMValueRef,
+) -> LLVMValueRef {
+    let signed = r#type.is_signed();
+    let builder = params.builder;
+
+    if r#type.is_float() {
+        return generate_float(params, operator, lhs, rhs);
+    }
+
+    unsafe {
+        match &operator {
+            Operator::Add => LLVMBuildAdd(builder, lhs, rhs, c"add_temp".as_ptr()),
+            Operator::Sub => LLVMBuildSub(builder, lhs, rhs, c"sub_temp".as_ptr()),
+            Operator::Mul => LLVMBuildMul(builder, lhs, rhs, c"mul_temp".as_ptr()),
+            Operator::Div if signed => LLVMBuildSDiv(builder, lhs, rhs, c"div_temp".as_ptr()),
+            Operator::Div => LLVMBuildUDiv(builder, lhs, rhs, c"div_temp".as_ptr()),
+            Operator::Mod if signed => LLVMBuildSRem(builder, lhs, rhs, c"rem_temp".as_ptr()),
+            Operator::Mod => LLVMBuildURem(builder, lhs, rhs, c"rem_temp".as_ptr()),
+            Operator::BitAnd => LLVMBuildAnd(builder, lhs, rhs, c"and_temp".as_ptr()),
+            Operator::BitOr => LLVMBuildOr(builder, lhs, rhs, c"or_temp".as_ptr()),
+            Operator::BitXor => LLVMBuildXor(builder, lhs, rhs, c"xor_temp".as_ptr()),
+            Operator::ShiftLeft => LLVMBuildShl(builder, lhs, rhs, c"shl_temp".as_ptr()),
+            //Signed values keep their sign when shifted right, unsigned values are filled with zeros
+            Operator::ShiftRight if signed => {
+                LLVMBuildAShr(builder, lhs, rhs, c"shr_temp".as_ptr())
+            }
+            Operator::ShiftRight => LLVMBuildLShr(builder, lhs, rhs, c"shr_temp".as_ptr()),
+            operator => LLVMBuildICmp(
+                builder,
+                int_predicate(operator, signed),
+                lhs,
+                rhs,
+                c"cmp_temp".as_ptr(),
+            ),
+        }
+    }
+}
+
+/// Predicate of an integer comparison, ordering comparisons depend on the signedness of the operands
+fn int_predicate(operator: &Operator, signed: bool) -> LLVMIntPredicate {
+    match (operator, signed) {
+        (Operator::Equal, _) => LLVMIntPredicate::LLVMIntEQ,
+        (Operator::NotEqual, _) => LLVMIntPredicate::LLVMIntNE,
+        (Operator::Less, true) => LLVMIntPredicate::LLVMIntSLT,
+        (Operator::Less, false) => LLVMIntPredicate::LLVMIntULT,
+        (Operator::LessEqual, true) => LLVMIntPredicate::LLVMIntSLE,
+        (Operator::LessEqual, false) => LLVMIntPredicate::LLVMIntULE,
+        (Operator::Greater, true) => LLVMIntPredicate::LLVMIntSGT,
+        (Operator::Greater, false) => LLVMIntPredicate::LLVMIntUGT,
+        (Operator::GreaterEqual, true) => LLVMIntPredicate::LLVMIntSGE,
+        (Operator::GreaterEqual, false) => LLVMIntPredicate::LLVMIntUGE,
+        (operator, _) => unreachable!("`{}` is not a comparison", operator),
+    }
+}
+
+/// Predicate of a float comparison, ordered predicates are false if either operand is NaN. Semantic analysis only
+/// allows arithmetic and comparisons on floats.
+fn real_predicate(operator: &Operator) -> LLVMRealPredicate {
+    match operator {
+        Operator::Equal => LLVMRealPredicate::LLVMRealOEQ,
+        Operator::NotEqual => LLVMRealPredicate::LLVMRealONE,
+        Operator::Less => LLVMRealPredicate::LLVMRealOLT,
+        Operator::LessEqual => LLVMRealPredicate::LLVMRealOLE,
+        Operator::Greater => LLVMRealPredicate::LLVMRealOGT,
+        Operator::GreaterEqual => LLVMRealPredicate::LLVMRealOGE,
+        operator => unreachable!("`{}` is not supported on floats", operator),
+    }
+}
+
+fn generate_float(
+    params: &mut ExpressionCodeGenParams,
+    operator: Operator,
+    lhs: LLVMValueRef,
+    rhs: LLVMValueRef,
+) -> LLVMValueRef {
+    let builder = params.builder;
+
+    unsafe {
+        match &operator {
+            Operator::Add => LLVMBuildFAdd(builder, lhs, rhs, c"add_temp".as_ptr()),
+            Operator::Sub => LLVMBuildFSub(builder, lhs, rhs, c"sub_temp".as_ptr()),
+            Operator::Mul => LLVMBuildFMul(builder, lhs, rhs, c"mul_temp".as_ptr()),
+            Operator::Div => LLVMBuildFDiv(builder, lhs, rhs, c"div_temp".as_ptr()),
+            Operator::Mod => LLVMBuildFRem(builder, lhs, rhs, c"rem_temp".as_ptr()),
+            operator => LLVMBuildFCmp(
+                builder,
+                real_predicate(operator),
+                lhs,
+                rhs,
+                c"cmp_temp".as_ptr(),
+            ),
         }
+    }
+}
+
+impl Binary {
+    /// `&&` and `||` only evaluate their right hand side if the left hand side does not already decide the
+    /// result, the two paths are merged with a phi node
+    fn generate_logical(
+        &self,
+        params: &mut ExpressionCodeGenParams,
+    ) -> Result<LLVMValueRef, CodeGenError> {
+        let bool_type = params.context.type_registry.get(&Type::Bool);
+        let is_and = self.operator == Operator::LogicalAnd;
+
+        let lhs = self.lhs.generate(params)?;
 
         unsafe {
-            match &self.operator {
-                Operator::Add => {
-                    LLVMBuildAdd(
-                        params.builder,
-                        lhs.val,
-                        rhs.val,
-                        b"add_temp\0".as_ptr().cast(),
-                    );
-                }
-                Operator::Sub => {
-                    LLVMBuildSub(
-                        params.builder,
-                        lhs.val,
-                        rhs.val,
-                        b"sub_temp\0".as_ptr().cast(),
-                    );
-                }
-                Operator::Mul => {
-                    LLVMBuildMul(
-                        params.builder,
-                        lhs.val,
-                        rhs.val,
-                        b"mul_temp\0".as_ptr().cast(),
-                    );
-                }
-                Operator::Less => {
-                    LLVMBuildICmp(
-                        params.builder,
-                        LLVMIntPredicate::LLVMIntSLE,
-                        lhs.val,
-                        rhs.val,
-                        b"le_temp\0".as_ptr().cast(),
-                    );
-                }
-                token => return Err(CodeGenError::UnsupportedOperator),
+            let context = *params.context.context;
+
+            //The left hand side may have added blocks itself, the branch starts from wherever it ended
+            let lhs_block = LLVMGetInsertBlock(params.builder);
+            let function = LLVMGetBasicBlockParent(lhs_block);
+
+            let rhs_block = LLVMAppendBasicBlockInContext(context, function, c"logic_rhs".as_ptr());
+            let merge_block =
+                LLVMAppendBasicBlockInContext(context, function, c"logic_merge".as_ptr());
+
+            if is_and {
+                LLVMBuildCondBr(params.builder, lhs, rhs_block, merge_block);
+            } else {
+                LLVMBuildCondBr(params.builder, lhs, merge_block, rhs_block);
             }
-        }
 
-        todo!()
+            LLVMPositionBuilderAtEnd(params.builder, rhs_block);
+            let rhs = self.rhs.generate(params)?;
+
+            let rhs_block = LLVMGetInsertBlock(params.builder);
+            LLVMBuildBr(params.builder, merge_block);
+
+            LLVMPositionBuilderAtEnd(params.builder, merge_block);
+            let phi = LLVMBuildPhi(params.builder, bool_type, c"logic_temp".as_ptr());
+
+            //Skipping the right hand side means `false` for `&&` and `true` for `||`
+            let short_circuit = LLVMConstInt(bool_type, (!is_and) as u64, 0);
+            let mut values = [short_circuit, rhs];
+            let mut blocks = [lhs_block, rhs_block];
+            LLVMAddIncoming(phi, values.as_mut_ptr(), blocks.as_mut_ptr(), 2);
+
+            Ok(phi)
+        }
     }
 }
@@ -0,0 +1,77 @@
+use crate::generation::expressions::{ExpressionCodeGen, ExpressionCodeGenParams};
+use crate::generation::CodeGenError;
+use crate::sema::hir::Call;
+use crate::sema::Type;
+use llvm_sys::core::{
+    LLVMBuildCall2, LLVMBuildFPExt, LLVMBuildSExt, LLVMBuildZExt, LLVMCountParamTypes,
+    LLVMGetReturnType, LLVMGetTypeKind,
+};
+use llvm_sys::prelude::LLVMValueRef;
+use llvm_sys::LLVMTypeKind;
+
+impl ExpressionCodeGen<'_> for Call {
+    fn generate(&self, params: &mut ExpressionCodeGenParams) -> Result<LLVMValueRef, CodeGenError> {
+        let (llvm_fn, function_type) = params.context.functions[self.function.0];
+        let fixed = unsafe { LLVMCountParamTypes(function_type) } as usize;
+
+        let mut args = Vec::with_capacity(self.args.len());
+        for (index, arg) in self.args.iter().enumerate() {
+            let value = arg.generate(params)?;
+
+            if index < fixed {
+                args.push(value);
+            } else {
+                args.push(promote(params, &arg.r#type, value));
+            }
+        }
+
+        //LLVM doesn't allow naming the result of a call to a void function
+        let return_type = unsafe { LLVMGetTypeKind(LLVMGetReturnType(function_type)) };
+        let name = if return_type == LLVMTypeKind::LLVMVoidTypeKind {
+            c"".as_ptr()
+        } else {
+            c"call_temp".as_ptr()
+        };
+
+        let val = unsafe {
+            LLVMBuildCall2(
+                params.builder,
+                function_type,
+                llvm_fn,
+                args.as_mut_ptr(),
+                args.len() as _,
+                name,
+            )
+        };
+
+        Ok(val)
+    }
+}
+
+/// Applies the default argument promotions of C to an argument passed as part of `...`, integers smaller than
+/// `i32` are extended and `f32` becomes `f64`
+fn promote(params: &ExpressionCodeGenParams, r#type: &Type, value: LLVMValueRef) -> LLVMValueRef {
+    let builder = params.builder;
+
+    unsafe {
+        match r#type {
+            Type::Float { bits: 32 } => {
+                let double = params.context.type_registry.get(&Type::Float { bits: 64 });
+                LLVMBuildFPExt(builder, value, double, c"promote_temp".as_ptr())
+            }
+            Type::Bool | Type::Int { bits: ..32, .. } => {
+                let int = params.context.type_registry.get(&Type::Int {
+                    signed: true,
+                    bits: 32,
+                });
+
+                if r#type.is_signed() {
+                    LLVMBuildSExt(builder, value, int, c"promote_temp".as_ptr())
+                } else {
+                    LLVMBuildZExt(builder, value, int, c"promote_temp".as_ptr())
+                }
+            }
+            _ => value,
+        }
+    }
+}
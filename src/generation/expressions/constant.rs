@@ -1,41 +1,11 @@
-use crate::generation::expressions::{CodeGenExpr, ExpressionCodeGen, ExpressionCodeGenParams};
-use crate::generation::{CodeGenContext, CodeGenError, Module};
-use crate::parser::{ConstantExpression, ConstantExpressionValue, Function, Type};
-use llvm_sys::core::LLVMConstInt;
+use llvm_sys::core::{LLVMConstInt, LLVMConstReal};
+use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
 
-impl<'a> ExpressionCodeGen<'a> for ConstantExpression {
-    fn generate(&self, params: &ExpressionCodeGenParams<'a>) -> Result<CodeGenExpr, CodeGenError> {
-        unsafe {
-            match self.value {
-                ConstantExpressionValue::Int(val) => {
-                    let ty = params
-                        .context
-                        .type_registry
-                        .get(&Type {
-                            is_pointer: false,
-                            name: "i64".to_owned(),
-                        })
-                        .unwrap();
-                    Ok(CodeGenExpr {
-                        val: LLVMConstInt(ty.type_ref, val as _, 0),
-                        r#type: ty,
-                    })
-                }
-                ConstantExpressionValue::UInt(val) => {
-                    let ty = params
-                        .context
-                        .type_registry
-                        .get(&Type {
-                            is_pointer: false,
-                            name: "i64".to_owned(),
-                        })
-                        .unwrap();
-                    Ok(CodeGenExpr {
-                        val: LLVMConstInt(ty.type_ref, val as _, 0),
-                        r#type: ty,
-                    })
-                }
-            }
-        }
-    }
+/// `val` is the bit pattern of the constant, negative values are already in two's complement
+pub fn generate_int(type_ref: LLVMTypeRef, val: u64) -> LLVMValueRef {
+    unsafe { LLVMConstInt(type_ref, val, 0) }
+}
+
+pub fn generate_float(type_ref: LLVMTypeRef, val: f64) -> LLVMValueRef {
+    unsafe { LLVMConstReal(type_ref, val) }
 }
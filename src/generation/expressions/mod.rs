@@ -1,42 +1,89 @@
 mod binary;
+mod call;
 mod constant;
+mod unary;
 mod variable;
 
-use crate::generation::type_registry::TypeDef;
-use crate::generation::{CodeGenContext, CodeGenError, Module};
-use crate::parser::{Expression, Function};
-use llvm_sys::prelude::{LLVMBasicBlockRef, LLVMBuilderRef, LLVMValueRef};
+pub use binary::generate_operation;
+
+use crate::generation::statements::append_block;
+use crate::generation::{CodeGenContext, CodeGenError};
+use crate::sema::hir::{Expression, ExpressionKind, Function};
+use llvm_sys::core::{
+    LLVMBuildGlobalStringPtr, LLVMBuildLoad2, LLVMGetUndef, LLVMPositionBuilderAtEnd,
+};
+use llvm_sys::prelude::{LLVMBuilderRef, LLVMValueRef};
+use std::ffi::CString;
 
 pub trait ExpressionCodeGen<'a> {
-    fn generate(&self, params: &ExpressionCodeGenParams<'a>) -> Result<CodeGenExpr, CodeGenError>;
+    fn generate(
+        &self,
+        params: &mut ExpressionCodeGenParams<'a>,
+    ) -> Result<LLVMValueRef, CodeGenError>;
 }
 
 pub struct ExpressionCodeGenParams<'a> {
     context: &'a mut CodeGenContext,
-    module: &'a mut Module,
     function: &'a Function,
-    block: LLVMBasicBlockRef,
     builder: LLVMBuilderRef,
 }
 
-#[derive(Clone, Debug)]
-pub struct CodeGenExpr {
-    val: LLVMValueRef,
-    r#type: TypeDef,
+impl<'a> ExpressionCodeGenParams<'a> {
+    pub fn new(
+        context: &'a mut CodeGenContext,
+        function: &'a Function,
+        builder: LLVMBuilderRef,
+    ) -> Self {
+        Self {
+            context,
+            function,
+            builder,
+        }
+    }
 }
 
 impl ExpressionCodeGen<'_> for Expression {
-    fn generate(&self, params: &ExpressionCodeGenParams) -> Result<CodeGenExpr, CodeGenError> {
-        match self {
-            Expression::Binary(exp) => exp.generate(params),
-            Expression::Unary(_) => {
-                todo!()
+    fn generate(&self, params: &mut ExpressionCodeGenParams) -> Result<LLVMValueRef, CodeGenError> {
+        let type_ref = params.context.type_registry.get(&self.r#type);
+
+        match &self.kind {
+            ExpressionKind::Int(val) => Ok(constant::generate_int(type_ref, *val)),
+            ExpressionKind::Float(val) => Ok(constant::generate_float(type_ref, *val)),
+            ExpressionKind::Local(local) => local.generate(params),
+            ExpressionKind::Binary(exp) => exp.generate(params),
+            ExpressionKind::Unary(exp) => exp.generate(params),
+            ExpressionKind::AddressOf(place) => place.generate(params),
+            ExpressionKind::Loop(exp) => {
+                let value = exp.generate_loop(params.context, params.function, params.builder)?;
+
+                match value {
+                    Some(value) => Ok(value),
+                    //A loop that is never left has no value, the code using it is unreachable and goes into a
+                    //block without predecessors
+                    None if !exp.has_break => unsafe {
+                        let block = append_block(params.context, params.builder, c"loop_after");
+                        LLVMPositionBuilderAtEnd(params.builder, block);
+                        Ok(LLVMGetUndef(type_ref))
+                    },
+                    None => unsafe { Ok(LLVMGetUndef(type_ref)) },
+                }
             }
-            Expression::Constant(exp) => exp.generate(params),
-            Expression::Variable(exp) => exp.generate(params),
-            Expression::Call(exp) => {
-                todo!()
+            ExpressionKind::Call(call) => call.generate(params),
+            ExpressionKind::String(string) => {
+                let string = CString::new(string.as_str())?;
+
+                Ok(unsafe {
+                    LLVMBuildGlobalStringPtr(params.builder, string.as_ptr(), c"str".as_ptr())
+                })
             }
+            ExpressionKind::Deref(pointer) => unsafe {
+                Ok(LLVMBuildLoad2(
+                    params.builder,
+                    type_ref,
+                    pointer.generate(params)?,
+                    c"deref_temp".as_ptr(),
+                ))
+            },
         }
     }
 }
@@ -0,0 +1,34 @@
+use crate::generation::expressions::{ExpressionCodeGen, ExpressionCodeGenParams};
+use crate::generation::CodeGenError;
+use crate::parser::Operator;
+use crate::sema::hir::{Place, Unary};
+use llvm_sys::core::{LLVMBuildFNeg, LLVMBuildNeg, LLVMBuildNot};
+use llvm_sys::prelude::LLVMValueRef;
+
+impl ExpressionCodeGen<'_> for Unary {
+    fn generate(&self, params: &mut ExpressionCodeGenParams) -> Result<LLVMValueRef, CodeGenError> {
+        let operand = self.operand.generate(params)?;
+
+        let val = unsafe {
+            match self.operator {
+                Operator::Sub if self.operand.r#type.is_float() => {
+                    LLVMBuildFNeg(params.builder, operand, c"neg_temp".as_ptr())
+                }
+                Operator::Sub => LLVMBuildNeg(params.builder, operand, c"neg_temp".as_ptr()),
+                _ => LLVMBuildNot(params.builder, operand, c"not_temp".as_ptr()),
+            }
+        };
+
+        Ok(val)
+    }
+}
+
+/// Generates the address of the place, the operand of `&` is not evaluated
+impl ExpressionCodeGen<'_> for Place {
+    fn generate(&self, params: &mut ExpressionCodeGenParams) -> Result<LLVMValueRef, CodeGenError> {
+        match self {
+            Place::Local(local) => Ok(params.context.variables[local.0]),
+            Place::Deref(pointer) => pointer.generate(params),
+        }
+    }
+}
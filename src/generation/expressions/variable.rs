@@ -1,14 +1,25 @@
-use crate::generation::expressions::{CodeGenExpr, ExpressionCodeGen, ExpressionCodeGenParams};
+use crate::generation::expressions::{ExpressionCodeGen, ExpressionCodeGenParams};
 use crate::generation::CodeGenError;
-use crate::parser::VariableExpression;
+use crate::sema::hir::LocalId;
+use llvm_sys::core::LLVMBuildLoad2;
+use llvm_sys::prelude::LLVMValueRef;
+use std::ffi::CString;
 
-impl ExpressionCodeGen<'_> for VariableExpression {
-    fn generate(&self, params: &ExpressionCodeGenParams) -> Result<CodeGenExpr, CodeGenError> {
-        Ok(params
-            .context
-            .variables
-            .get(&self.name)
-            .ok_or(CodeGenError::InvalidVariable(self.name.clone()))?
-            .clone())
+impl ExpressionCodeGen<'_> for LocalId {
+    fn generate(&self, params: &mut ExpressionCodeGenParams) -> Result<LLVMValueRef, CodeGenError> {
+        let local = &params.function.locals[self.0];
+        let type_ref = params.context.type_registry.get(&local.r#type);
+
+        let name = CString::new(local.name.as_str())?;
+        let val = unsafe {
+            LLVMBuildLoad2(
+                params.builder,
+                type_ref,
+                params.context.variables[self.0],
+                name.as_ptr(),
+            )
+        };
+
+        Ok(val)
     }
 }
@@ -1,27 +1,90 @@
+use crate::generation::statements::StatementCodeGen;
 use crate::generation::type_registry::TypeRegistry;
-use crate::generation::{CodeGenError, Context, Module};
-use crate::parser::Function;
-use llvm_sys::core::{LLVMAddFunction, LLVMAppendBasicBlockInContext, LLVMFunctionType};
-use llvm_sys::prelude::LLVMTypeRef;
+use crate::generation::{CodeGenContext, CodeGenError, Module};
+use crate::sema::hir::Function;
+use llvm_sys::core::{
+    LLVMAddFunction, LLVMAppendBasicBlockInContext, LLVMBuildAlloca, LLVMBuildRetVoid,
+    LLVMBuildStore, LLVMBuildUnreachable, LLVMFunctionType, LLVMGetBasicBlockTerminator,
+    LLVMGetInsertBlock, LLVMGetParam, LLVMPositionBuilderAtEnd, LLVMSetFunctionCallConv,
+    LLVMSetLinkage,
+};
+use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
+use llvm_sys::{LLVMCallConv, LLVMLinkage};
 use std::ffi::CString;
 
-pub fn generate_function(
-    context: &Context,
-    type_registry: &TypeRegistry,
+/// Adds `function` to the module without a body
+pub fn declare_function(
+    context: &mut CodeGenContext,
     module: &mut Module,
     function: &Function,
 ) -> Result<(), CodeGenError> {
-    let function_type = make_function_type(type_registry, function)?;
+    let function_type = make_function_type(&context.type_registry, function)?;
     let name = CString::new(function.name.as_str())?;
 
     let llvm_fn = unsafe { LLVMAddFunction(**module, name.as_ptr(), function_type) };
 
+    //Resolved by the linker, or by the JIT from the symbols of the running process
+    if function.is_extern {
+        unsafe {
+            LLVMSetLinkage(llvm_fn, LLVMLinkage::LLVMExternalLinkage);
+            LLVMSetFunctionCallConv(llvm_fn, LLVMCallConv::LLVMCCallConv as _);
+        }
+    }
+
+    context.functions.push((llvm_fn, function_type));
+
+    Ok(())
+}
+
+/// Generates the body of the declared `llvm_fn`
+pub fn generate_function(
+    context: &mut CodeGenContext,
+    function: &Function,
+    llvm_fn: LLVMValueRef,
+) -> Result<(), CodeGenError> {
     let Some(body) = &function.body else {
         return Ok(());
     };
 
-    let entry =
-        unsafe { LLVMAppendBasicBlockInContext(**context, llvm_fn, b"entry\0".as_ptr().cast()) };
+    let builder = context.builder;
+
+    unsafe {
+        let entry = LLVMAppendBasicBlockInContext(*context.context, llvm_fn, c"entry".as_ptr());
+        LLVMPositionBuilderAtEnd(builder, entry);
+    }
+
+    //Every local gets its stack slot in the entry block, parameters are stored there so they can be treated
+    //like any other variable
+    context.variables.clear();
+    for (index, local) in function.locals.iter().enumerate() {
+        let local_name = CString::new(local.name.as_str())?;
+        let type_ref = context.type_registry.get(&local.r#type);
+
+        let ptr = unsafe {
+            let ptr = LLVMBuildAlloca(builder, type_ref, local_name.as_ptr());
+            if index < function.params {
+                LLVMBuildStore(builder, LLVMGetParam(llvm_fn, index as _), ptr);
+            }
+            ptr
+        };
+
+        context.variables.push(ptr);
+    }
+
+    body.generate(context, function, builder)?;
+
+    //Semantic analysis makes sure only void functions fall off the end of their body, the end of other
+    //functions can only be left open after a `loop` that is never left
+    let terminator = unsafe { LLVMGetBasicBlockTerminator(LLVMGetInsertBlock(builder)) };
+    if terminator.is_null() {
+        unsafe {
+            if function.return_type.is_void() {
+                LLVMBuildRetVoid(builder);
+            } else {
+                LLVMBuildUnreachable(builder);
+            }
+        }
+    }
 
     Ok(())
 }
@@ -31,23 +94,20 @@ fn make_function_type(
     function: &Function,
 ) -> Result<LLVMTypeRef, CodeGenError> {
     let mut args = function
-        .params
-        .iter()
-        .map(|arg| {
-            Ok(type_registry
-                .get(&arg.r#type)
-                .ok_or(CodeGenError::InvalidType(arg.r#type.name.clone()))?
-                .type_ref)
-        })
-        .collect::<Result<Vec<_>, CodeGenError>>()?;
-
-    let return_type = type_registry
-        .get(&function.return_type)
-        .ok_or(CodeGenError::InvalidType(function.return_type.name.clone()))?
-        .type_ref;
-
-    let function_type =
-        unsafe { LLVMFunctionType(return_type, args.as_mut_ptr(), args.len() as _, 0) };
+        .param_types()
+        .map(|r#type| type_registry.get(r#type))
+        .collect::<Vec<_>>();
+
+    let return_type = type_registry.get(&function.return_type);
+
+    let function_type = unsafe {
+        LLVMFunctionType(
+            return_type,
+            args.as_mut_ptr(),
+            args.len() as _,
+            function.variadic as _,
+        )
+    };
     if function_type.is_null() {
         Err(CodeGenError::LLVMError)
     } else {
@@ -0,0 +1,191 @@
+use crate::generation::target::initialize_targets;
+use crate::generation::{take_message, verify_module, CodeGenContext, CodeGenError, Module};
+use crate::parser::{Element, Function, SourceFile};
+use llvm_sys::core::{
+    LLVMAddFunction, LLVMAppendBasicBlockInContext, LLVMBuildCall2, LLVMBuildIntCast2,
+    LLVMBuildRet, LLVMConstInt, LLVMCountParamTypes, LLVMFunctionType, LLVMGetNamedFunction,
+    LLVMGetParam, LLVMGetParamTypes, LLVMGetReturnType, LLVMGetTypeKind, LLVMGlobalGetValueType,
+    LLVMInt32TypeInContext, LLVMInt8TypeInContext, LLVMPointerType, LLVMPositionBuilderAtEnd,
+};
+use llvm_sys::execution_engine::{
+    LLVMCreateMCJITCompilerForModule, LLVMDisposeExecutionEngine, LLVMGetFunctionAddress,
+    LLVMInitializeMCJITCompilerOptions, LLVMLinkInMCJIT, LLVMMCJITCompilerOptions,
+};
+use llvm_sys::prelude::LLVMTypeRef;
+use llvm_sys::LLVMTypeKind;
+use std::ffi::{c_char, CString};
+use std::{mem, ptr};
+
+const ENTRY_POINT: &str = "__maga_main";
+
+type EntryPoint = extern "C" fn(i32, *const *const c_char) -> i32;
+
+/// Compiles `module` in memory and calls its `main` function with `args`, returns the exit code of the program
+pub fn run_main(
+    context: &CodeGenContext,
+    module: Module,
+    source_file: &SourceFile,
+    args: &[String],
+) -> Result<i32, CodeGenError> {
+    build_entry_point(context, &module, source_file)?;
+    //The shim is added after `generate` verified the module, so check it again before handing it to the JIT
+    verify_module(&module)?;
+
+    initialize_targets();
+
+    let mut options = unsafe { mem::zeroed::<LLVMMCJITCompilerOptions>() };
+    let mut engine = ptr::null_mut();
+    let mut message = ptr::null_mut();
+
+    let failed = unsafe {
+        LLVMLinkInMCJIT();
+        LLVMInitializeMCJITCompilerOptions(&mut options, mem::size_of_val(&options));
+        LLVMCreateMCJITCompilerForModule(
+            &mut engine,
+            *module,
+            &mut options,
+            mem::size_of_val(&options),
+            &mut message,
+        )
+    };
+
+    let message = unsafe { take_message(message) };
+    if failed != 0 {
+        return Err(CodeGenError::JitError(message));
+    }
+
+    let entry_point = CString::new(ENTRY_POINT)?;
+    let address = unsafe { LLVMGetFunctionAddress(engine, entry_point.as_ptr()) };
+    if address == 0 {
+        unsafe { LLVMDisposeExecutionEngine(engine) };
+        return Err(CodeGenError::JitError(format!(
+            "Failed to resolve {}",
+            ENTRY_POINT
+        )));
+    }
+
+    let args = args
+        .iter()
+        .map(|arg| CString::new(arg.as_str()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut argv = args.iter().map(|arg| arg.as_ptr()).collect::<Vec<_>>();
+    argv.push(ptr::null());
+
+    let exit_code = unsafe {
+        let entry_point = mem::transmute::<u64, EntryPoint>(address);
+        let exit_code = entry_point(args.len() as _, argv.as_ptr());
+
+        //The engine owns the module, so this also frees it
+        LLVMDisposeExecutionEngine(engine);
+        exit_code
+    };
+
+    Ok(exit_code)
+}
+
+/// Adds a function with the C signature `int(int, char**)` that forwards to `main`,
+/// so we can call it with a fixed signature no matter how `main` was declared
+fn build_entry_point(
+    context: &CodeGenContext,
+    module: &Module,
+    source_file: &SourceFile,
+) -> Result<(), CodeGenError> {
+    let main_fn = find_main(source_file).ok_or(CodeGenError::MissingMain)?;
+    let main = unsafe { LLVMGetNamedFunction(**module, c"main".as_ptr()) };
+    if main.is_null() {
+        return Err(CodeGenError::MissingMain);
+    }
+
+    let is_kind =
+        |r#type: LLVMTypeRef, kind: LLVMTypeKind| unsafe { LLVMGetTypeKind(r#type) } == kind;
+
+    unsafe {
+        let main_type = LLVMGlobalGetValueType(main);
+        let return_type = LLVMGetReturnType(main_type);
+
+        let mut param_types = vec![ptr::null_mut(); LLVMCountParamTypes(main_type) as usize];
+        LLVMGetParamTypes(main_type, param_types.as_mut_ptr());
+
+        let valid_params = match param_types.as_slice() {
+            [] => true,
+            [argc, argv] => {
+                is_kind(*argc, LLVMTypeKind::LLVMIntegerTypeKind)
+                    && is_kind(*argv, LLVMTypeKind::LLVMPointerTypeKind)
+            }
+            _ => false,
+        };
+        let valid_return = is_kind(return_type, LLVMTypeKind::LLVMIntegerTypeKind)
+            || is_kind(return_type, LLVMTypeKind::LLVMVoidTypeKind);
+
+        //Point at the parameters if they are the problem, otherwise at the whole function
+        if !valid_params || !valid_return {
+            let span = match main_fn.params.as_slice() {
+                [first, .., last] if !valid_params => first.span.to(last.span),
+                [param] if !valid_params => param.span,
+                _ => main_fn.span,
+            };
+            return Err(CodeGenError::InvalidMainSignature(span));
+        }
+
+        let int_type = LLVMInt32TypeInContext(*context.context);
+        let argv_type = match param_types.as_slice() {
+            [_, argv] => *argv,
+            _ => LLVMPointerType(LLVMInt8TypeInContext(*context.context), 0),
+        };
+
+        let mut entry_params = [int_type, argv_type];
+        let entry_type = LLVMFunctionType(int_type, entry_params.as_mut_ptr(), 2, 0);
+
+        let entry_point = CString::new(ENTRY_POINT)?;
+        let entry_fn = LLVMAddFunction(**module, entry_point.as_ptr(), entry_type);
+        let block = LLVMAppendBasicBlockInContext(*context.context, entry_fn, c"entry".as_ptr());
+
+        let builder = context.builder;
+        LLVMPositionBuilderAtEnd(builder, block);
+
+        let mut args = match param_types.as_slice() {
+            [argc, _] => vec![
+                LLVMBuildIntCast2(
+                    builder,
+                    LLVMGetParam(entry_fn, 0),
+                    *argc,
+                    1,
+                    c"argc".as_ptr(),
+                ),
+                LLVMGetParam(entry_fn, 1),
+            ],
+            _ => Vec::new(),
+        };
+
+        let name = if is_kind(return_type, LLVMTypeKind::LLVMVoidTypeKind) {
+            c"".as_ptr()
+        } else {
+            c"exit_code".as_ptr()
+        };
+        let exit_code = LLVMBuildCall2(
+            builder,
+            main_type,
+            main,
+            args.as_mut_ptr(),
+            args.len() as _,
+            name,
+        );
+
+        if is_kind(return_type, LLVMTypeKind::LLVMVoidTypeKind) {
+            LLVMBuildRet(builder, LLVMConstInt(int_type, 0, 0));
+        } else {
+            let exit_code =
+                LLVMBuildIntCast2(builder, exit_code, int_type, 1, c"exit_code_cast".as_ptr());
+            LLVMBuildRet(builder, exit_code);
+        }
+    }
+
+    Ok(())
+}
+
+fn find_main(source_file: &SourceFile) -> Option<&Function> {
+    source_file.iter().find_map(|element| {
+        let Element::Function(function) = element;
+        (function.name == "main").then_some(function)
+    })
+}
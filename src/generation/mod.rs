@@ -1,18 +1,27 @@
 mod expressions;
 mod function;
+mod jit;
 mod statement;
 mod statements;
+mod target;
 mod type_registry;
-mod util;
 
-use crate::generation::expressions::CodeGenExpr;
-use crate::generation::function::generate_function;
-use crate::generation::type_registry::{TypeDef, TypeRegistry};
-use crate::parser::{Element, Parser};
-use llvm_sys::core::{LLVMContextCreate, LLVMDumpModule, LLVMModuleCreateWithNameInContext};
+use crate::diagnostic::Diagnostic;
+use crate::generation::function::{declare_function, generate_function};
+pub use crate::generation::jit::run_main;
+pub use crate::generation::target::{OutputKind, TargetMachine};
+use crate::generation::type_registry::TypeRegistry;
+use crate::sema::hir::Program;
+use crate::span::Span;
+use llvm_sys::analysis::{LLVMVerifierFailureAction, LLVMVerifyModule};
+use llvm_sys::bit_writer::LLVMWriteBitcodeToMemoryBuffer;
+use llvm_sys::core::{
+    LLVMContextCreate, LLVMCreateBuilderInContext, LLVMDisposeBuilder, LLVMDisposeMemoryBuffer,
+    LLVMDisposeMessage, LLVMGetBufferSize, LLVMGetBufferStart, LLVMModuleCreateWithNameInContext,
+    LLVMPrintModuleToString,
+};
 use llvm_sys::prelude::*;
-use std::collections::HashMap;
-use std::ffi::{CString, NulError};
+use std::ffi::{c_char, CStr, CString, NulError};
 use std::ops::Deref;
 use thiserror::Error;
 
@@ -30,23 +39,39 @@ impl Deref for Context {
 
 #[derive(Debug, Error)]
 pub enum CodeGenError {
-    #[error("LLVM error occured")]
+    #[error("LLVM error occurred")]
     LLVMError,
 
-    #[error("Nul error occured:")]
+    #[error("Nul error occurred: {0}")]
     NulError(#[from] NulError),
 
-    #[error("Invalid type: {0}")]
-    InvalidType(String),
+    #[error("Module verification failed: {0}")]
+    VerificationFailed(String),
 
-    #[error("Invalid variable: {0}")]
-    InvalidVariable(String),
+    #[error("Target error: {0}")]
+    TargetError(String),
 
-    #[error("Uunsupported operator")]
-    UnsupportedOperator,
+    #[error("No main function found")]
+    MissingMain,
 
-    #[error("Types are not eual: lhs: {0:?}, rhs: {1:?}")]
-    TypesNotEqual(TypeDef, TypeDef),
+    #[error("Invalid signature for `main`")]
+    InvalidMainSignature(Span),
+
+    #[error("JIT error: {0}")]
+    JitError(String),
+}
+
+impl From<&CodeGenError> for Diagnostic {
+    fn from(err: &CodeGenError) -> Self {
+        let diagnostic = Diagnostic::error(err.to_string());
+
+        match err {
+            CodeGenError::InvalidMainSignature(span) => diagnostic.with_label(*span, "").with_note(
+                "`main` must take either no parameters or `(argc: <integer>, argv: <pointer>)` and return an integer or nothing",
+            ),
+            _ => diagnostic,
+        }
+    }
 }
 
 impl Context {
@@ -84,11 +109,41 @@ impl Deref for Module {
     }
 }
 
+impl Module {
+    pub fn print_to_string(&self) -> String {
+        unsafe { take_message(LLVMPrintModuleToString(self.module)) }
+    }
+
+    pub fn write_bitcode_to_memory(&self) -> Result<Vec<u8>, CodeGenError> {
+        unsafe {
+            let buffer = LLVMWriteBitcodeToMemoryBuffer(self.module);
+            if buffer.is_null() {
+                return Err(CodeGenError::LLVMError);
+            }
+
+            Ok(take_memory_buffer(buffer))
+        }
+    }
+}
+
 pub struct CodeGenContext {
     context: Context,
     type_registry: TypeRegistry,
-    variables: HashMap<String, CodeGenExpr>,
-    variable_scopes: HashMap<u32, Vec<String>>,
+    builder: LLVMBuilderRef,
+    //Declared functions of the program and their types, indexed by `FunctionId`
+    functions: Vec<(LLVMValueRef, LLVMTypeRef)>,
+    //Stack slots of the locals of the current function, indexed by `LocalId`
+    variables: Vec<LLVMValueRef>,
+    loop_targets: Vec<LoopTarget>,
+}
+
+/// Blocks `continue` and `break` jump to for one of the loops enclosing the current statement
+pub struct LoopTarget {
+    continue_block: LLVMBasicBlockRef,
+    //`None` for loops that are never left
+    break_block: Option<LLVMBasicBlockRef>,
+    //Values `break` leaves a `loop` expression with and the blocks they come from
+    incoming: Vec<(LLVMValueRef, LLVMBasicBlockRef)>,
 }
 
 impl CodeGenContext {
@@ -96,38 +151,82 @@ impl CodeGenContext {
         let context = Context::new()?;
         let type_registry = TypeRegistry::new(&context);
 
+        let builder = unsafe { LLVMCreateBuilderInContext(*context) };
+        if builder.is_null() {
+            return Err(CodeGenError::LLVMError);
+        }
+
         Ok(Self {
             context,
             type_registry,
-            variables: HashMap::new(),
-            variable_scopes: HashMap::new(),
+            builder,
+            functions: Vec::new(),
+            variables: Vec::new(),
+            loop_targets: Vec::new(),
         })
     }
 
-    pub fn generate(&mut self, module: &mut Module, parser: &Parser) -> Result<(), CodeGenError> {
-        let source_file = parser.root();
-
-        for element in &source_file.0 {
-            match element {
-                Element::Function(function) => {
-                    generate_function(&self.context, &self.type_registry, module, function)?;
-                }
-                _ => {}
-            }
+    pub fn generate(&mut self, module: &mut Module, program: &Program) -> Result<(), CodeGenError> {
+        //Every function is declared before any body is generated, so calls can refer to functions defined later
+        for function in &program.functions {
+            declare_function(self, module, function)?;
         }
 
-        unsafe {
-            LLVMDumpModule(**module);
+        for (index, function) in program.functions.iter().enumerate() {
+            generate_function(self, function, self.functions[index].0)?;
         }
 
-        Ok(())
-    }
-
-    pub fn context(&self) -> &Context {
-        &self.context
+        verify_module(module)
     }
 
     pub fn context_mut(&mut self) -> &mut Context {
         &mut self.context
     }
 }
+
+impl Drop for CodeGenContext {
+    fn drop(&mut self) {
+        unsafe { LLVMDisposeBuilder(self.builder) }
+    }
+}
+
+fn verify_module(module: &Module) -> Result<(), CodeGenError> {
+    let mut message = std::ptr::null_mut();
+
+    let failed = unsafe {
+        LLVMVerifyModule(
+            **module,
+            LLVMVerifierFailureAction::LLVMReturnStatusAction,
+            &mut message,
+        )
+    };
+
+    let text = unsafe { take_message(message) };
+    if failed != 0 {
+        Err(CodeGenError::VerificationFailed(text))
+    } else {
+        Ok(())
+    }
+}
+
+/// Copies a message allocated by LLVM into a `String` and frees it
+unsafe fn take_message(message: *mut c_char) -> String {
+    if message.is_null() {
+        return String::new();
+    }
+
+    let text = CStr::from_ptr(message).to_string_lossy().into_owned();
+    LLVMDisposeMessage(message);
+    text
+}
+
+/// Copies the contents of a memory buffer allocated by LLVM and frees it
+unsafe fn take_memory_buffer(buffer: LLVMMemoryBufferRef) -> Vec<u8> {
+    let bytes = std::slice::from_raw_parts(
+        LLVMGetBufferStart(buffer).cast::<u8>(),
+        LLVMGetBufferSize(buffer),
+    )
+    .to_vec();
+    LLVMDisposeMemoryBuffer(buffer);
+    bytes
+}
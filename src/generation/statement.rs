@@ -1,19 +1,58 @@
+use crate::generation::expressions::{ExpressionCodeGen, ExpressionCodeGenParams};
 use crate::generation::statements::StatementCodeGen;
-use crate::generation::{CodeGenContext, CodeGenError, Module};
-use crate::parser::{Function, Statement};
-use llvm_sys::LLVMBasicBlock;
+use crate::generation::{CodeGenContext, CodeGenError};
+use crate::sema::hir::{Block, Function, Statement};
+use llvm_sys::core::{LLVMBuildBr, LLVMGetBasicBlockTerminator, LLVMGetInsertBlock};
+use llvm_sys::prelude::LLVMBuilderRef;
 
 impl StatementCodeGen for Statement {
     fn generate(
         &self,
         context: &mut CodeGenContext,
-        module: &mut Module,
         function: &Function,
-        block: LLVMBasicBlock,
+        builder: LLVMBuilderRef,
     ) -> Result<(), CodeGenError> {
         match self {
-            Statement::Return(stat) => {}
-            Statement::Declaration(stat) => {}
+            Statement::Return(stat) => stat.generate(context, function, builder),
+            Statement::Declaration(stat) => stat.generate(context, function, builder),
+            Statement::Block(block) => block.generate(context, function, builder),
+            Statement::Assign(stat) => stat.generate(context, function, builder),
+            Statement::If(stat) => stat.generate(context, function, builder),
+            Statement::While(stat) => stat.generate(context, function, builder),
+            Statement::Loop(stat) => stat.generate(context, function, builder),
+            Statement::Expression(exp) => {
+                exp.generate(&mut ExpressionCodeGenParams::new(
+                    context, function, builder,
+                ))?;
+                Ok(())
+            }
+            Statement::Break(stat) => stat.generate(context, function, builder),
+            Statement::Continue(target) => {
+                let target = context
+                    .loop_targets
+                    .get(*target)
+                    .expect("`continue` to a loop that does not enclose it");
+                unsafe { LLVMBuildBr(builder, target.continue_block) };
+                Ok(())
+            }
+        }
+    }
+}
+
+impl StatementCodeGen for Block {
+    fn generate(
+        &self,
+        context: &mut CodeGenContext,
+        function: &Function,
+        builder: LLVMBuilderRef,
+    ) -> Result<(), CodeGenError> {
+        for statement in &self.statements {
+            //Nothing can follow a terminator in the same basic block, the rest of the block is unreachable
+            if !unsafe { LLVMGetBasicBlockTerminator(LLVMGetInsertBlock(builder)) }.is_null() {
+                break;
+            }
+
+            statement.generate(context, function, builder)?;
         }
 
         Ok(())
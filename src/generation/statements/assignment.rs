@@ -1,16 +1,32 @@
+use crate::generation::expressions::{ExpressionCodeGen, ExpressionCodeGenParams};
 use crate::generation::statements::StatementCodeGen;
-use crate::generation::{CodeGenContext, CodeGenError, Module};
-use crate::parser::{AssignmentStatement, Function};
-use llvm_sys::LLVMBasicBlock;
+use crate::generation::{CodeGenContext, CodeGenError};
+use crate::sema::hir::{Declaration, Function};
+use llvm_sys::core::{LLVMBuildStore, LLVMConstNull};
+use llvm_sys::prelude::LLVMBuilderRef;
 
-impl StatementCodeGen for AssignmentStatement {
+impl StatementCodeGen for Declaration {
     fn generate(
         &self,
         context: &mut CodeGenContext,
-        module: &mut Module,
         function: &Function,
-        block: LLVMBasicBlock,
+        builder: LLVMBuilderRef,
     ) -> Result<(), CodeGenError> {
-        todo!()
+        let value = match &self.value {
+            Some(value) => value.generate(&mut ExpressionCodeGenParams::new(
+                context, function, builder,
+            ))?,
+            //Variables declared without an initializer start out zeroed
+            None => {
+                let r#type = &function.locals[self.local.0].r#type;
+                unsafe { LLVMConstNull(context.type_registry.get(r#type)) }
+            }
+        };
+
+        unsafe {
+            LLVMBuildStore(builder, value, context.variables[self.local.0]);
+        }
+
+        Ok(())
     }
 }
@@ -0,0 +1,85 @@
+use crate::generation::expressions::{ExpressionCodeGen, ExpressionCodeGenParams};
+use crate::generation::statements::StatementCodeGen;
+use crate::generation::{CodeGenContext, CodeGenError};
+use crate::sema::hir::{Function, If};
+use llvm_sys::core::{
+    LLVMAppendBasicBlockInContext, LLVMBuildBr, LLVMBuildCondBr, LLVMGetBasicBlockParent,
+    LLVMGetBasicBlockTerminator, LLVMGetInsertBlock, LLVMPositionBuilderAtEnd,
+};
+use llvm_sys::prelude::{LLVMBasicBlockRef, LLVMBuilderRef};
+use std::ffi::CStr;
+
+impl StatementCodeGen for If {
+    fn generate(
+        &self,
+        context: &mut CodeGenContext,
+        function: &Function,
+        builder: LLVMBuilderRef,
+    ) -> Result<(), CodeGenError> {
+        let condition = self.condition.generate(&mut ExpressionCodeGenParams::new(
+            context, function, builder,
+        ))?;
+
+        let llvm_context = *context.context;
+        let llvm_fn = unsafe { LLVMGetBasicBlockParent(LLVMGetInsertBlock(builder)) };
+        let append_block = |name: &CStr| unsafe {
+            LLVMAppendBasicBlockInContext(llvm_context, llvm_fn, name.as_ptr())
+        };
+
+        let then_block = append_block(c"then");
+        //Without an `else` the false branch continues after the `if`
+        let (else_block, mut merge_block) = match self.else_block {
+            Some(_) => (append_block(c"else"), None),
+            None => {
+                let merge_block = append_block(c"endif");
+                (merge_block, Some(merge_block))
+            }
+        };
+
+        unsafe {
+            LLVMBuildCondBr(builder, condition, then_block, else_block);
+        }
+
+        let mut open_ends = Vec::new();
+
+        unsafe { LLVMPositionBuilderAtEnd(builder, then_block) };
+        self.then_block.generate(context, function, builder)?;
+        open_ends.extend(open_end(builder));
+
+        if let Some(block) = &self.else_block {
+            unsafe { LLVMPositionBuilderAtEnd(builder, else_block) };
+            block.generate(context, function, builder)?;
+            open_ends.extend(open_end(builder));
+        }
+
+        //If no branch falls through there is nothing to merge, the builder stays in a terminated block so the
+        //statements after the `if` are skipped
+        if !open_ends.is_empty() && merge_block.is_none() {
+            merge_block = Some(append_block(c"endif"));
+        }
+
+        if let Some(merge_block) = merge_block {
+            unsafe {
+                for block in open_ends {
+                    LLVMPositionBuilderAtEnd(builder, block);
+                    LLVMBuildBr(builder, merge_block);
+                }
+
+                LLVMPositionBuilderAtEnd(builder, merge_block);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The block the builder is in if it doesn't end in a terminator yet, nested statements may have moved the
+/// builder away from the block the branch started in
+fn open_end(builder: LLVMBuilderRef) -> Option<LLVMBasicBlockRef> {
+    unsafe {
+        let block = LLVMGetInsertBlock(builder);
+        LLVMGetBasicBlockTerminator(block)
+            .is_null()
+            .then_some(block)
+    }
+}
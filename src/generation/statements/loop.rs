@@ -0,0 +1,200 @@
+use crate::generation::expressions::{ExpressionCodeGen, ExpressionCodeGenParams};
+use crate::generation::statements::StatementCodeGen;
+use crate::generation::{CodeGenContext, CodeGenError, LoopTarget};
+use crate::sema::hir::{Block, Break, Function, Loop, While};
+use llvm_sys::core::{
+    LLVMAddIncoming, LLVMAppendBasicBlockInContext, LLVMBuildBr, LLVMBuildCondBr, LLVMBuildPhi,
+    LLVMGetBasicBlockParent, LLVMGetBasicBlockTerminator, LLVMGetInsertBlock,
+    LLVMPositionBuilderAtEnd,
+};
+use llvm_sys::prelude::{LLVMBasicBlockRef, LLVMBuilderRef, LLVMValueRef};
+use std::ffi::CStr;
+
+impl StatementCodeGen for While {
+    fn generate(
+        &self,
+        context: &mut CodeGenContext,
+        function: &Function,
+        builder: LLVMBuilderRef,
+    ) -> Result<(), CodeGenError> {
+        let cond_block = append_block(context, builder, c"while_cond");
+        let body_block = append_block(context, builder, c"while_body");
+        let end_block = append_block(context, builder, c"while_end");
+
+        unsafe {
+            LLVMBuildBr(builder, cond_block);
+            LLVMPositionBuilderAtEnd(builder, cond_block);
+        }
+
+        let condition = self.condition.generate(&mut ExpressionCodeGenParams::new(
+            context, function, builder,
+        ))?;
+
+        unsafe {
+            LLVMBuildCondBr(builder, condition, body_block, end_block);
+        }
+
+        //`continue` checks the condition again
+        let target = LoopTarget {
+            continue_block: cond_block,
+            break_block: Some(end_block),
+            incoming: Vec::new(),
+        };
+        generate_body(context, function, builder, &self.body, body_block, target)?;
+
+        unsafe {
+            LLVMPositionBuilderAtEnd(builder, end_block);
+        }
+
+        Ok(())
+    }
+}
+
+impl StatementCodeGen for Loop {
+    fn generate(
+        &self,
+        context: &mut CodeGenContext,
+        function: &Function,
+        builder: LLVMBuilderRef,
+    ) -> Result<(), CodeGenError> {
+        self.generate_loop(context, function, builder)?;
+
+        Ok(())
+    }
+}
+
+impl Loop {
+    /// Generates the loop and continues after it, returns the value the loop is left with unless its type is
+    /// `()` or it is never left
+    pub fn generate_loop(
+        &self,
+        context: &mut CodeGenContext,
+        function: &Function,
+        builder: LLVMBuilderRef,
+    ) -> Result<Option<LLVMValueRef>, CodeGenError> {
+        let body_block = append_block(context, builder, c"loop_body");
+        //A loop without `break` is never left, so it doesn't get a block to continue with afterward
+        let end_block = self
+            .has_break
+            .then(|| append_block(context, builder, c"loop_end"));
+
+        unsafe {
+            LLVMBuildBr(builder, body_block);
+        }
+
+        let target = LoopTarget {
+            continue_block: body_block,
+            break_block: end_block,
+            incoming: Vec::new(),
+        };
+        let mut target = generate_body(context, function, builder, &self.body, body_block, target)?;
+
+        //Otherwise the builder stays in the terminated end of the body and the statements after the loop are
+        //skipped
+        let Some(end_block) = end_block else {
+            return Ok(None);
+        };
+
+        unsafe {
+            LLVMPositionBuilderAtEnd(builder, end_block);
+        }
+
+        if self.r#type.is_void() {
+            return Ok(None);
+        }
+
+        let type_ref = context.type_registry.get(&self.r#type);
+        let (mut values, mut blocks): (Vec<_>, Vec<_>) = target.incoming.drain(..).unzip();
+
+        let phi = unsafe {
+            let phi = LLVMBuildPhi(builder, type_ref, c"loop_value".as_ptr());
+            LLVMAddIncoming(
+                phi,
+                values.as_mut_ptr(),
+                blocks.as_mut_ptr(),
+                values.len() as _,
+            );
+            phi
+        };
+
+        Ok(Some(phi))
+    }
+}
+
+impl StatementCodeGen for Break {
+    fn generate(
+        &self,
+        context: &mut CodeGenContext,
+        function: &Function,
+        builder: LLVMBuilderRef,
+    ) -> Result<(), CodeGenError> {
+        let value = match &self.value {
+            Some(value) => Some(value.generate(&mut ExpressionCodeGenParams::new(
+                context, function, builder,
+            ))?),
+            None => None,
+        };
+
+        let target = context
+            .loop_targets
+            .get_mut(self.target)
+            .expect("`break` to a loop that does not enclose it");
+        let block = target
+            .break_block
+            .expect("`break` in a loop that cannot be left");
+
+        unsafe {
+            //The value may have added blocks itself, the phi needs the block the branch is in
+            if let Some(value) = value {
+                target.incoming.push((value, LLVMGetInsertBlock(builder)));
+            }
+            LLVMBuildBr(builder, block);
+        }
+
+        Ok(())
+    }
+}
+
+/// Generates the body of a loop in `body_block`, the end of the body jumps back to the start of the loop.
+/// Returns the target with the values of the `break`s that left it.
+fn generate_body(
+    context: &mut CodeGenContext,
+    function: &Function,
+    builder: LLVMBuilderRef,
+    body: &Block,
+    body_block: LLVMBasicBlockRef,
+    target: LoopTarget,
+) -> Result<LoopTarget, CodeGenError> {
+    let continue_block = target.continue_block;
+
+    unsafe {
+        LLVMPositionBuilderAtEnd(builder, body_block);
+    }
+
+    context.loop_targets.push(target);
+    let result = body.generate(context, function, builder);
+    let target = context
+        .loop_targets
+        .pop()
+        .expect("loop target was pushed above");
+    result?;
+
+    unsafe {
+        if LLVMGetBasicBlockTerminator(LLVMGetInsertBlock(builder)).is_null() {
+            LLVMBuildBr(builder, continue_block);
+        }
+    }
+
+    Ok(target)
+}
+
+pub fn append_block(
+    context: &CodeGenContext,
+    builder: LLVMBuilderRef,
+    name: &CStr,
+) -> LLVMBasicBlockRef {
+    unsafe {
+        let llvm_fn = LLVMGetBasicBlockParent(LLVMGetInsertBlock(builder));
+        LLVMAppendBasicBlockInContext(*context.context, llvm_fn, name.as_ptr())
+    }
+}
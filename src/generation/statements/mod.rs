@@ -1,16 +1,20 @@
-use crate::generation::{CodeGenContext, CodeGenError, Module};
-use crate::parser::Function;
-use llvm_sys::LLVMBasicBlock;
+use crate::generation::{CodeGenContext, CodeGenError};
+use crate::sema::hir::Function;
+use llvm_sys::prelude::LLVMBuilderRef;
 
 mod assignment;
+mod r#if;
+mod r#loop;
+mod reassignment;
 mod r#return;
 
+pub use r#loop::append_block;
+
 pub trait StatementCodeGen {
     fn generate(
         &self,
         context: &mut CodeGenContext,
-        module: &mut Module,
         function: &Function,
-        block: LLVMBasicBlock,
+        builder: LLVMBuilderRef,
     ) -> Result<(), CodeGenError>;
 }
@@ -0,0 +1,39 @@
+use crate::generation::expressions::{
+    generate_operation, ExpressionCodeGen, ExpressionCodeGenParams,
+};
+use crate::generation::statements::StatementCodeGen;
+use crate::generation::{CodeGenContext, CodeGenError};
+use crate::sema::hir::{Assign, Function};
+use llvm_sys::core::{LLVMBuildLoad2, LLVMBuildStore};
+use llvm_sys::prelude::LLVMBuilderRef;
+
+impl StatementCodeGen for Assign {
+    fn generate(
+        &self,
+        context: &mut CodeGenContext,
+        function: &Function,
+        builder: LLVMBuilderRef,
+    ) -> Result<(), CodeGenError> {
+        let type_ref = context.type_registry.get(&self.value.r#type);
+        let mut params = ExpressionCodeGenParams::new(context, function, builder);
+
+        //The place is evaluated once, also for compound assignments
+        let ptr = self.place.generate(&mut params)?;
+        let value = self.value.generate(&mut params)?;
+
+        let value = match self.operator {
+            Some(operator) => {
+                let current =
+                    unsafe { LLVMBuildLoad2(builder, type_ref, ptr, c"load_temp".as_ptr()) };
+                generate_operation(&mut params, operator, &self.value.r#type, current, value)
+            }
+            None => value,
+        };
+
+        unsafe {
+            LLVMBuildStore(builder, value, ptr);
+        }
+
+        Ok(())
+    }
+}
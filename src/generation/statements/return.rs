@@ -1 +1,32 @@
+use crate::generation::expressions::{ExpressionCodeGen, ExpressionCodeGenParams};
+use crate::generation::statements::StatementCodeGen;
+use crate::generation::{CodeGenContext, CodeGenError};
+use crate::sema::hir::{Function, Return};
+use llvm_sys::core::{LLVMBuildRet, LLVMBuildRetVoid};
+use llvm_sys::prelude::LLVMBuilderRef;
 
+impl StatementCodeGen for Return {
+    fn generate(
+        &self,
+        context: &mut CodeGenContext,
+        function: &Function,
+        builder: LLVMBuilderRef,
+    ) -> Result<(), CodeGenError> {
+        let Some(value) = &self.value else {
+            unsafe {
+                LLVMBuildRetVoid(builder);
+            }
+            return Ok(());
+        };
+
+        let value = value.generate(&mut ExpressionCodeGenParams::new(
+            context, function, builder,
+        ))?;
+
+        unsafe {
+            LLVMBuildRet(builder, value);
+        }
+
+        Ok(())
+    }
+}
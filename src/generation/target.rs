@@ -0,0 +1,185 @@
+use crate::generation::{take_memory_buffer, take_message, CodeGenError, Module};
+use llvm_sys::core::LLVMSetTarget;
+use llvm_sys::target::{
+    LLVMDisposeTargetData, LLVMSetModuleDataLayout, LLVM_InitializeAllAsmParsers,
+    LLVM_InitializeAllAsmPrinters, LLVM_InitializeAllTargetInfos, LLVM_InitializeAllTargetMCs,
+    LLVM_InitializeAllTargets,
+};
+use llvm_sys::target_machine::{
+    LLVMCodeGenFileType, LLVMCodeGenOptLevel, LLVMCodeModel, LLVMCreateTargetDataLayout,
+    LLVMCreateTargetMachine, LLVMDisposeTargetMachine, LLVMGetDefaultTargetTriple,
+    LLVMGetHostCPUFeatures, LLVMGetHostCPUName, LLVMGetTargetFromTriple, LLVMRelocMode,
+    LLVMTargetMachineEmitToFile, LLVMTargetMachineEmitToMemoryBuffer, LLVMTargetMachineRef,
+};
+use std::ffi::CString;
+use std::ops::Deref;
+use std::path::Path;
+use std::ptr;
+use std::sync::Once;
+
+static INITIALIZE_TARGETS: Once = Once::new();
+
+/// Registers every target LLVM was built with, safe to call more than once
+pub fn initialize_targets() {
+    INITIALIZE_TARGETS.call_once(|| unsafe {
+        LLVM_InitializeAllTargetInfos();
+        LLVM_InitializeAllTargets();
+        LLVM_InitializeAllTargetMCs();
+        LLVM_InitializeAllAsmPrinters();
+        LLVM_InitializeAllAsmParsers();
+    });
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputKind {
+    Assembly,
+    Object,
+}
+
+impl From<OutputKind> for LLVMCodeGenFileType {
+    fn from(kind: OutputKind) -> Self {
+        match kind {
+            OutputKind::Assembly => LLVMCodeGenFileType::LLVMAssemblyFile,
+            OutputKind::Object => LLVMCodeGenFileType::LLVMObjectFile,
+        }
+    }
+}
+
+pub struct TargetMachine {
+    target_machine: LLVMTargetMachineRef,
+    triple: CString,
+}
+
+impl Deref for TargetMachine {
+    type Target = LLVMTargetMachineRef;
+
+    fn deref(&self) -> &Self::Target {
+        &self.target_machine
+    }
+}
+
+impl TargetMachine {
+    /// Creates a target machine for `triple`, or for the host when no triple is given
+    pub fn new(triple: Option<&str>) -> Result<Self, CodeGenError> {
+        initialize_targets();
+
+        //Host specific cpu features only make sense when we are not cross compiling
+        let (triple, cpu, features) = match triple {
+            Some(triple) => (triple.to_owned(), "generic".to_owned(), String::new()),
+            None => unsafe {
+                (
+                    take_message(LLVMGetDefaultTargetTriple()),
+                    take_message(LLVMGetHostCPUName()),
+                    take_message(LLVMGetHostCPUFeatures()),
+                )
+            },
+        };
+
+        let triple = CString::new(triple)?;
+        let cpu = CString::new(cpu)?;
+        let features = CString::new(features)?;
+
+        let mut target = ptr::null_mut();
+        let mut message = ptr::null_mut();
+
+        let failed = unsafe { LLVMGetTargetFromTriple(triple.as_ptr(), &mut target, &mut message) };
+        let message = unsafe { take_message(message) };
+        if failed != 0 {
+            return Err(CodeGenError::TargetError(message));
+        }
+
+        let target_machine = unsafe {
+            LLVMCreateTargetMachine(
+                target,
+                triple.as_ptr(),
+                cpu.as_ptr(),
+                features.as_ptr(),
+                LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+                LLVMRelocMode::LLVMRelocPIC,
+                LLVMCodeModel::LLVMCodeModelDefault,
+            )
+        };
+
+        if target_machine.is_null() {
+            Err(CodeGenError::TargetError(format!(
+                "Failed to create target machine for {}",
+                triple.to_string_lossy()
+            )))
+        } else {
+            Ok(Self {
+                target_machine,
+                triple,
+            })
+        }
+    }
+
+    /// Sets the triple and data layout of `module` to the ones of this target machine
+    pub fn configure_module(&self, module: &mut Module) {
+        unsafe {
+            LLVMSetTarget(**module, self.triple.as_ptr());
+
+            let data_layout = LLVMCreateTargetDataLayout(self.target_machine);
+            LLVMSetModuleDataLayout(**module, data_layout);
+            LLVMDisposeTargetData(data_layout);
+        }
+    }
+
+    pub fn emit_to_file(
+        &self,
+        module: &Module,
+        path: &Path,
+        kind: OutputKind,
+    ) -> Result<(), CodeGenError> {
+        let path = CString::new(path.to_string_lossy().as_bytes())?;
+        let mut message = ptr::null_mut();
+
+        let failed = unsafe {
+            LLVMTargetMachineEmitToFile(
+                self.target_machine,
+                **module,
+                path.as_ptr(),
+                kind.into(),
+                &mut message,
+            )
+        };
+
+        let message = unsafe { take_message(message) };
+        if failed != 0 {
+            Err(CodeGenError::TargetError(message))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn emit_to_memory(
+        &self,
+        module: &Module,
+        kind: OutputKind,
+    ) -> Result<Vec<u8>, CodeGenError> {
+        let mut message = ptr::null_mut();
+        let mut buffer = ptr::null_mut();
+
+        let failed = unsafe {
+            LLVMTargetMachineEmitToMemoryBuffer(
+                self.target_machine,
+                **module,
+                kind.into(),
+                &mut message,
+                &mut buffer,
+            )
+        };
+
+        let message = unsafe { take_message(message) };
+        if failed != 0 {
+            Err(CodeGenError::TargetError(message))
+        } else {
+            Ok(unsafe { take_memory_buffer(buffer) })
+        }
+    }
+}
+
+impl Drop for TargetMachine {
+    fn drop(&mut self) {
+        unsafe { LLVMDisposeTargetMachine(self.target_machine) }
+    }
+}
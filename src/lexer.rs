@@ -1,8 +1,218 @@
+use crate::diagnostic::Diagnostic;
 use crate::parser::CompileError;
-use logos::Logos;
+use crate::span::Span;
+use logos::{FilterResult, Logos};
+use thiserror::Error;
 
-#[derive(Logos, Clone, Debug, PartialEq, Eq)]
+/// Error produced by logos, turned into a `LexError` once the span is known
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum LexErrorKind {
+    #[default]
+    UnknownCharacter,
+    MalformedNumber,
+    IntegerTooLarge,
+    UnterminatedComment,
+    UnterminatedString,
+    InvalidEscape,
+}
+
+#[derive(Debug, Error)]
+pub enum LexError {
+    #[error("Unknown character `{0}`")]
+    UnknownCharacter(char, Span),
+
+    #[error("Malformed number `{0}`")]
+    MalformedNumber(String, Span),
+
+    #[error("Integer literal `{0}` is too large")]
+    IntegerTooLarge(String, Span),
+
+    #[error("Unterminated block comment")]
+    UnterminatedComment(Span),
+
+    #[error("Unterminated string literal")]
+    UnterminatedString(Span),
+
+    #[error("Invalid escape sequence in `{0}`")]
+    InvalidEscape(String, Span),
+}
+
+impl LexError {
+    fn new(kind: LexErrorKind, slice: &str, span: Span) -> Self {
+        match kind {
+            LexErrorKind::UnknownCharacter => {
+                LexError::UnknownCharacter(slice.chars().next().unwrap_or_default(), span)
+            }
+            LexErrorKind::MalformedNumber => LexError::MalformedNumber(slice.to_owned(), span),
+            LexErrorKind::IntegerTooLarge => LexError::IntegerTooLarge(slice.to_owned(), span),
+            //Only point at the opening `/*`, the span reaches to the end of the file
+            LexErrorKind::UnterminatedComment => {
+                LexError::UnterminatedComment(Span::new(span.start, span.start + 2))
+            }
+            LexErrorKind::UnterminatedString => LexError::UnterminatedString(span),
+            LexErrorKind::InvalidEscape => LexError::InvalidEscape(slice.to_owned(), span),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            LexError::UnknownCharacter(_, span)
+            | LexError::MalformedNumber(_, span)
+            | LexError::IntegerTooLarge(_, span)
+            | LexError::UnterminatedComment(span)
+            | LexError::UnterminatedString(span)
+            | LexError::InvalidEscape(_, span) => *span,
+        }
+    }
+}
+
+impl From<&LexError> for Diagnostic {
+    fn from(err: &LexError) -> Self {
+        let diagnostic = Diagnostic::error(err.to_string());
+
+        match err {
+            LexError::UnknownCharacter(_, span) => {
+                diagnostic.with_label(*span, "not valid in source code")
+            }
+            LexError::MalformedNumber(_, span) => {
+                diagnostic.with_label(*span, "invalid digit or suffix")
+            }
+            LexError::IntegerTooLarge(_, span) => {
+                diagnostic.with_label(*span, "does not fit into 64 bits")
+            }
+            LexError::UnterminatedComment(span) => diagnostic
+                .with_label(*span, "comment starts here")
+                .with_help("block comments nest, every `/*` needs a matching `*/`"),
+            LexError::UnterminatedString(span) => diagnostic
+                .with_label(*span, "missing the closing `\"`")
+                .with_help("strings cannot span multiple lines, use `\\n` for a line break"),
+            LexError::InvalidEscape(_, span) => diagnostic
+                .with_label(*span, "contains an unknown escape sequence")
+                .with_help("supported escapes are `\\n`, `\\t`, `\\r`, `\\\\` and `\\\"`"),
+        }
+    }
+}
+
+/// Suffixes naming the type of an integer literal
+const INTEGER_SUFFIXES: [&str; 8] = ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"];
+
+/// Value of an integer literal, `suffix` is the type written directly after it, e.g. `u8` in `5u8`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntLiteral {
+    pub value: u64,
+    pub suffix: Option<String>,
+}
+
+/// Parses decimal, hexadecimal (`0x`), octal (`0o`) and binary (`0b`) literals, digits may be separated by `_`
+fn parse_integer(slice: &str) -> Result<IntLiteral, LexErrorKind> {
+    let (digits, suffix) = match INTEGER_SUFFIXES
+        .iter()
+        .find_map(|suffix| slice.strip_suffix(suffix))
+    {
+        Some(digits) => (digits, Some(slice[digits.len()..].to_owned())),
+        None => (slice, None),
+    };
+
+    let (digits, radix) = match digits.get(..2) {
+        Some("0x") => (&digits[2..], 16),
+        Some("0o") => (&digits[2..], 8),
+        Some("0b") => (&digits[2..], 2),
+        _ => (digits, 10),
+    };
+
+    let digits = digits.replace('_', "");
+    if digits.is_empty() || !digits.chars().all(|char| char.is_digit(radix)) {
+        return Err(LexErrorKind::MalformedNumber);
+    }
+
+    let value = u64::from_str_radix(&digits, radix).map_err(|_| LexErrorKind::IntegerTooLarge)?;
+    Ok(IntLiteral { value, suffix })
+}
+
+/// Skips a block comment including all comments nested inside of it
+fn block_comment(lex: &mut logos::Lexer<Token>) -> FilterResult<(), LexErrorKind> {
+    let remainder = lex.remainder();
+    let mut depth = 1;
+    let mut pos = 0;
+
+    while pos < remainder.len() {
+        if remainder[pos..].starts_with("/*") {
+            depth += 1;
+            pos += 2;
+        } else if remainder[pos..].starts_with("*/") {
+            depth -= 1;
+            pos += 2;
+
+            if depth == 0 {
+                lex.bump(pos);
+                return FilterResult::Skip;
+            }
+        } else {
+            pos += remainder[pos..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    lex.bump(remainder.len());
+    FilterResult::Error(LexErrorKind::UnterminatedComment)
+}
+
+/// Contents of a string literal without the quotes and with its escape sequences replaced
+fn string_literal(lex: &mut logos::Lexer<Token>) -> Result<String, LexErrorKind> {
+    let slice = lex.slice();
+    let mut chars = slice[1..slice.len() - 1].chars();
+    let mut string = String::new();
+
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            string.push(char);
+            continue;
+        }
+
+        string.push(match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            _ => return Err(LexErrorKind::InvalidEscape),
+        });
+    }
+
+    Ok(string)
+}
+
+/// Text of a `///` comment without the slashes and the first space following them
+fn doc_comment(lex: &mut logos::Lexer<Token>) -> String {
+    let text = &lex.slice()[3..];
+    text.strip_prefix(' ').unwrap_or(text).to_owned()
+}
+
+/// Value of a float literal, `suffix` is the type written directly after it, e.g. `f32` in `2.0f32`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FloatLiteral {
+    pub value: f64,
+    pub suffix: Option<String>,
+}
+
+fn parse_float(slice: &str) -> Result<FloatLiteral, LexErrorKind> {
+    let (digits, suffix) = match slice.strip_suffix("f32").or(slice.strip_suffix("f64")) {
+        Some(digits) => (digits, Some(slice[digits.len()..].to_owned())),
+        None => (slice, None),
+    };
+
+    let value = digits
+        .replace('_', "")
+        .parse()
+        .map_err(|_| LexErrorKind::MalformedNumber)?;
+    Ok(FloatLiteral { value, suffix })
+}
+
+#[derive(Logos, Clone, Debug, PartialEq)]
 #[logos(skip r"[ \t\n\f]+")]
+//`////` is a regular comment just like in Rust
+#[logos(skip r"//([^/\n][^\n]*)?")]
+#[logos(skip r"////[^\n]*")]
+#[logos(error = LexErrorKind)]
 pub enum Token {
     #[token("let")]
     Let,
@@ -11,6 +221,8 @@ pub enum Token {
 
     #[token("fun")]
     Fun,
+    #[token("extern")]
+    Extern,
     #[token("unsafe")]
     Unsafe,
     #[token("const")]
@@ -20,6 +232,8 @@ pub enum Token {
     Return,
     #[token("if")]
     If,
+    #[token("else")]
+    Else,
     #[token("while")]
     While,
     #[token("break")]
@@ -35,6 +249,8 @@ pub enum Token {
     Semicolon,
     #[token(".")]
     Point,
+    #[token("...")]
+    Ellipsis,
     #[token(",")]
     Comma,
 
@@ -84,51 +300,85 @@ pub enum Token {
     #[token(">=")]
     GreaterEqual,
 
+    #[token("==")]
+    Equal,
+    #[token("!=")]
+    NotEqual,
+
+    #[token("&&")]
+    LogicalAnd,
+    #[token("||")]
+    LogicalOr,
+    #[token("!")]
+    Not,
+
+    #[token("&")]
+    BitAnd,
+    #[token("|")]
+    BitOr,
+    #[token("^")]
+    BitXor,
+    #[token("~")]
+    BitNot,
+    #[token("<<")]
+    ShiftLeft,
+    #[token(">>")]
+    ShiftRight,
+
     #[regex("[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice().to_owned())]
     Identifier(String),
 
-    #[regex("-(0|[1-9][0-9]*)", |lex| lex.slice().parse::<i64>().unwrap())]
-    IntLiteral(i64),
+    /// String literal without the quotes, escape sequences are already replaced
+    #[regex(r#""([^"\\\n]|\\.)*""#, string_literal)]
+    #[regex(r#""([^"\\\n]|\\.)*"#, |_| Err(LexErrorKind::UnterminatedString))]
+    StringLiteral(String),
+
+    /// Loop label like `'outer`, without the `'`
+    #[regex("'[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice()[1..].to_owned())]
+    Label(String),
 
-    #[regex("(0|[1-9][0-9]*)", |lex| lex.slice().parse::<u64>().unwrap())]
-    UIntLiteral(u64),
+    //Negative numbers are a negation applied to a literal, so `a -1` is a subtraction
+    #[regex("[0-9][0-9a-zA-Z_]*", |lex| parse_integer(lex.slice()))]
+    IntLiteral(IntLiteral),
+
+    //Takes precedence over integers for literals like `1e3` which match both
+    #[regex(r"[0-9][0-9_]*\.[0-9][0-9_]*([eE][+-]?[0-9_]+)?(f32|f64)?", |lex| parse_float(lex.slice()), priority = 10)]
+    #[regex(r"[0-9][0-9_]*[eE][+-]?[0-9_]+(f32|f64)?", |lex| parse_float(lex.slice()), priority = 10)]
+    FloatLiteral(FloatLiteral),
+
+    #[regex(r"///([^/\n][^\n]*)?", doc_comment)]
+    DocComment(String),
+
+    //Never produced, block comments are skipped or turn into an error
+    #[token("/*", block_comment)]
+    BlockComment,
+
+    //Never produced by logos, stands in for text that could not be lexed, its `LexError` is reported separately
+    Error,
+
+    //Never produced by logos, appended by `Lexer::tokens` so the parser always has a token to look at
+    Eof,
 }
 
-macro_rules! generate_as_fn {
+macro_rules! generate_expect_fn {
     ($fn_name:ident, $variant:path) => {
-        pub fn $fn_name(&self) -> Result<(), CompileError> {
-            match self {
-                $variant => Ok(()),
-                token => Err(CompileError::new(format!(
-                    "Expected {}, found {:?}",
-                    stringify!($variant),
-                    token
-                ))),
+        /// Consumes the next token if it matches, otherwise it is left in the stream
+        pub fn $fn_name(&mut self) -> Result<Span, CompileError> {
+            match self.peek() {
+                $variant => {
+                    self.pos += 1;
+                    Ok(self.prev_span())
+                }
+                _ => Err(self.unexpected(stringify!($variant))),
             }
         }
     };
 }
 
-impl Token {
-    pub fn as_ident(&self) -> Result<&str, CompileError> {
-        match self {
-            Token::Identifier(ident) => Ok(ident.as_str()),
-            token => Err(CompileError::new(format!(
-                "Expected ident, found {:?}",
-                token
-            ))),
-        }
-    }
-
-    generate_as_fn!(as_lparen, Token::LParen);
-    generate_as_fn!(as_rparen, Token::RParen);
-    generate_as_fn!(as_lbrace, Token::LBrace);
-    generate_as_fn!(as_rbrace, Token::RBrace);
-    generate_as_fn!(as_double_colon, Token::DoubleColon);
-    generate_as_fn!(as_comma, Token::Comma);
-    generate_as_fn!(as_assign, Token::Assign);
-    generate_as_fn!(as_semicolon, Token::Semicolon);
-    generate_as_fn!(as_return, Token::Return);
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
 }
 
 pub struct Lexer<'a> {
@@ -141,63 +391,170 @@ impl<'a> Lexer<'a> {
         Self { lexer }
     }
 
-    pub fn print_tokens(self) {
-        for token in self.lexer {
-            let Ok(token) = token else { continue };
+    /// Lexes the whole source, invalid text becomes a `Token::Error` and lexing continues after it, so the parser
+    /// still sees the rest of the file and every invalid span is reported at once
+    pub fn tokens(self) -> Tokens {
+        let source = self.lexer.source();
+        let end = source.len();
+
+        let mut tokens = Vec::new();
+        let mut lex_errors = Vec::new();
 
-            println!("{:?}", token);
+        for (token, span) in self.lexer.spanned() {
+            let token = token.unwrap_or_else(|kind| {
+                lex_errors.push(LexError::new(
+                    kind,
+                    &source[span.clone()],
+                    span.clone().into(),
+                ));
+                Token::Error
+            });
+
+            tokens.push(SpannedToken {
+                token,
+                span: span.into(),
+            });
         }
-    }
 
-    pub fn tokens(self) -> Result<Tokens, ()> {
-        let tokens = self.lexer.into_iter().collect::<Result<Vec<Token>, ()>>()?;
-        Ok(Tokens { tokens, pos: 0 })
+        tokens.push(SpannedToken {
+            token: Token::Eof,
+            span: Span::new(end, end),
+        });
+
+        Tokens {
+            tokens,
+            pos: 0,
+            lex_errors,
+            errors: Vec::new(),
+        }
     }
 }
 
+/// Token stream that always ends with a `Token::Eof`, peeking or consuming past the end keeps returning it
 #[derive(Debug)]
 pub struct Tokens {
-    tokens: Vec<Token>,
+    tokens: Vec<SpannedToken>,
     pos: usize,
+    lex_errors: Vec<LexError>,
+    //Errors the parser recovered from
+    errors: Vec<CompileError>,
 }
 
 impl Tokens {
-    pub fn tokens(&self) -> &[Token] {
+    pub fn tokens(&self) -> &[SpannedToken] {
         &self.tokens
     }
 
-    pub fn get_peek(&self) -> Option<&Token> {
-        self.tokens.get(self.pos)
+    fn current(&self) -> &SpannedToken {
+        //The stream is never empty as it always ends with `Token::Eof`
+        &self.tokens[self.pos.min(self.tokens.len() - 1)]
     }
 
     pub fn peek(&self) -> &Token {
-        &self.tokens[self.pos]
+        &self.current().token
     }
 
-    pub fn try_peek(&self) -> Result<&Token, CompileError> {
-        if self.has_more() {
-            Ok(self.peek())
-        } else {
-            Err(CompileError::new(
-                "No more elements available in token stream".to_owned(),
-            ))
-        }
+    /// Span of the next token, an empty span at the end of the file once there are no more tokens
+    pub fn peek_span(&self) -> Span {
+        self.current().span
+    }
+
+    /// Error for the next token not being what the parser expected
+    pub fn unexpected(&self, expected: &str) -> CompileError {
+        let message = match self.peek() {
+            Token::Eof => format!("Unexpected end of file, expected {}", expected),
+            token => format!("Expected {}, found {:?}", expected, token),
+        };
+
+        CompileError::new(message, self.peek_span())
+    }
+
+    /// Span of the last consumed token
+    pub fn prev_span(&self) -> Span {
+        self.pos
+            .checked_sub(1)
+            .and_then(|pos| self.tokens.get(pos))
+            .map(|token| token.span)
+            .unwrap_or_default()
+    }
+
+    /// Span reaching from `start` to the end of the last consumed token
+    pub fn span_from(&self, start: Span) -> Span {
+        start.to(self.prev_span())
     }
 
     pub fn has_more(&self) -> bool {
-        self.pos < self.tokens.len()
+        *self.peek() != Token::Eof
     }
 
-    pub fn get(&mut self) -> &Token {
-        if !self.has_more() {
-            panic!("No more tokens in token stream")
-        } else {
+    /// Consumes the next token, at the end of the file `Token::Eof` is returned without advancing
+    pub fn get(&mut self) -> &SpannedToken {
+        if self.has_more() {
             self.pos += 1;
             &self.tokens[self.pos - 1]
+        } else {
+            self.current()
         }
     }
 
+    pub fn expect_ident(&mut self) -> Result<String, CompileError> {
+        match self.peek() {
+            Token::Identifier(ident) => {
+                let ident = ident.clone();
+                self.pos += 1;
+                Ok(ident)
+            }
+            _ => Err(self.unexpected("ident")),
+        }
+    }
+
+    generate_expect_fn!(expect_lparen, Token::LParen);
+    generate_expect_fn!(expect_rparen, Token::RParen);
+    generate_expect_fn!(expect_lbrace, Token::LBrace);
+    generate_expect_fn!(expect_rbrace, Token::RBrace);
+    generate_expect_fn!(expect_double_colon, Token::DoubleColon);
+    generate_expect_fn!(expect_comma, Token::Comma);
+    generate_expect_fn!(expect_assign, Token::Assign);
+    generate_expect_fn!(expect_semicolon, Token::Semicolon);
+    generate_expect_fn!(expect_return, Token::Return);
+    generate_expect_fn!(expect_if, Token::If);
+    generate_expect_fn!(expect_while, Token::While);
+    generate_expect_fn!(expect_loop, Token::Loop);
+    generate_expect_fn!(expect_break, Token::Break);
+    generate_expect_fn!(expect_continue, Token::Continue);
+    generate_expect_fn!(expect_fun, Token::Fun);
+
     pub fn add_pos(&mut self, amount: usize) {
-        self.pos += amount
+        self.pos = (self.pos + amount).min(self.tokens.len() - 1)
+    }
+
+    /// Records an error the parser recovered from
+    /// Records an error the parser recovered from. A missing `}` is seen by every scope that is still open, so
+    /// an error at the same place as the previous one is only recorded once
+    ///
+    /// Running into a `Token::Error` is not recorded at all, the lex error already describes what is wrong there.
+    pub fn report(&mut self, error: CompileError) {
+        let at_lex_error = self
+            .lex_errors
+            .iter()
+            .any(|lex_error| lex_error.span() == error.span());
+
+        if !at_lex_error && self.errors.last().map(CompileError::span) != Some(error.span()) {
+            self.errors.push(error);
+        }
+    }
+
+    /// Diagnostics for the lex errors and the errors the parser recovered from, in the order they appear in the file
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        let mut diagnostics = self
+            .lex_errors
+            .drain(..)
+            .map(|err| Diagnostic::from(&err))
+            .chain(self.errors.drain(..).map(|err| Diagnostic::from(&err)))
+            .collect::<Vec<_>>();
+        diagnostics
+            .sort_by_key(|diagnostic| diagnostic.primary_label().map(|label| label.span.start));
+
+        diagnostics
     }
 }
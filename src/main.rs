@@ -1,25 +1,71 @@
-use crate::generation::{CodeGenContext, Context, Module};
-use crate::lexer::Lexer;
-use crate::parser::Parser;
-use std::fs;
+use crate::cli::{Command, DiagnosticOptions, USAGE};
+use crate::diagnostic::{Diagnostic, Emitter};
+use crate::driver::DriverError;
+use std::env;
+use std::process::ExitCode;
 
+mod cli;
+mod diagnostic;
+mod driver;
 mod generation;
 mod lexer;
 mod parser;
+mod sema;
+mod span;
 
-fn main() {
-    let file = fs::read_to_string("files/example.maga").expect("Failed to read file");
+/// Exit code of `maga run` when the program could not be compiled, outside the range programs usually return
+/// and the same value `env` and `timeout` use for their own failures
+const RUN_FAILED: u8 = 125;
 
-    let lexer = Lexer::new(&file);
-    let mut tokens = lexer.tokens().expect("Failed to tokenize");
-    println!("{:?}", tokens);
+fn main() -> ExitCode {
+    let command = match Command::parse(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        }
+    };
 
-    let parser = Parser::new(&mut tokens);
-    parser.print_ast();
+    match command {
+        Command::Help => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        Command::Version => {
+            println!("maga {}", env!("CARGO_PKG_VERSION"));
+            ExitCode::SUCCESS
+        }
+        Command::Compile(options) => {
+            let emitter = emitter(&options.diagnostics);
+            match driver::compile(&options, &emitter) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    report(&emitter, err);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::Run(options) => {
+            let emitter = emitter(&options.diagnostics);
+            match driver::run(&options, &emitter) {
+                //Like returning from `main` in C, only the low 8 bits of the exit code reach the parent process
+                Ok(exit_code) => ExitCode::from((exit_code & 0xff) as u8),
+                Err(err) => {
+                    report(&emitter, err);
+                    ExitCode::from(RUN_FAILED)
+                }
+            }
+        }
+    }
+}
 
-    println!("\n\n\n");
+fn emitter(options: &DiagnosticOptions) -> Emitter {
+    Emitter::new(options.color, options.error_format)
+}
 
-    let mut context = CodeGenContext::new().unwrap();
-    let mut module = context.context_mut().create_module("example").unwrap();
-    context.generate(&mut module, &parser).unwrap();
+/// Emits errors that were not reported as diagnostics yet
+fn report(emitter: &Emitter, err: DriverError) {
+    if !matches!(err, DriverError::Aborted) {
+        emitter.emit(&Diagnostic::error(err.to_string()), None);
+    }
 }
@@ -1,12 +1,18 @@
 use crate::lexer::{Token, Tokens};
 use crate::parser::expression::Expression;
-use crate::parser::{CompileError, FromTokenStream, LiteralType};
+use crate::parser::{CompileError, FromTokenStream, Type};
+use crate::span::Span;
 
+/// `let name: type = rhs;` or `var name: type = rhs;`, the type is optional if there is an initializer and
+/// only `var` may leave out the initializer
 #[derive(Debug)]
 pub struct AssignmentStatement {
     pub mutable: bool,
     pub ident: String,
-    pub rhs: Expression,
+    pub ident_span: Span,
+    pub r#type: Option<Type>,
+    pub rhs: Option<Expression>,
+    pub span: Span,
 }
 
 impl FromTokenStream for AssignmentStatement {
@@ -14,16 +20,49 @@ impl FromTokenStream for AssignmentStatement {
     where
         Self: Sized,
     {
-        let mutable = *tokens.get() == Token::Var;
-        let ident = tokens.get().as_ident()?.to_owned();
-        tokens.get().as_assign()?;
-        let rhs = Expression::from_token_stream(tokens)?;
-        tokens.get().as_semicolon()?;
+        let start = tokens.peek_span();
+
+        let mutable = tokens.get().token == Token::Var;
+        let ident_span = tokens.peek_span();
+        let ident = tokens.expect_ident()?;
+
+        let mut r#type = None;
+        if *tokens.peek() == Token::DoubleColon {
+            tokens.add_pos(1);
+            r#type = Some(Type::from_token_stream(tokens)?);
+        }
+
+        let mut rhs = None;
+        if *tokens.peek() == Token::Semicolon {
+            if !mutable {
+                return Err(CompileError::new(
+                    format!("`let` binding `{}` needs an initializer", ident),
+                    tokens.span_from(start),
+                ));
+            }
+            if r#type.is_none() {
+                return Err(CompileError::new(
+                    format!(
+                        "`var` binding `{}` without an initializer needs a type",
+                        ident
+                    ),
+                    tokens.span_from(start),
+                ));
+            }
+        } else {
+            tokens.expect_assign()?;
+            rhs = Some(Expression::from_token_stream(tokens)?);
+        }
+
+        tokens.expect_semicolon()?;
 
         Ok(Self {
             mutable,
             ident,
+            ident_span,
+            r#type,
             rhs,
+            span: tokens.span_from(start),
         })
     }
 }
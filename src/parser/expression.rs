@@ -1,9 +1,9 @@
-use crate::lexer::{Token, Tokens};
-use crate::parser::{CompileError, FromTokenStream, LiteralType};
-use lazy_static::lazy_static;
-use std::collections::HashMap;
+use crate::lexer::{FloatLiteral, IntLiteral, SpannedToken, Token, Tokens};
+use crate::parser::{CompileError, FromTokenStream, LoopStatement, Type};
+use crate::span::Span;
+use std::fmt::{Display, Formatter};
 
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub enum Operator {
     Add,
     AddAssign,
@@ -25,12 +25,26 @@ pub enum Operator {
 
     Greater,
     GreaterEqual,
-}
 
-impl Operator {}
+    Equal,
+    NotEqual,
+
+    BitAnd,
+    BitOr,
+    BitXor,
+
+    ShiftLeft,
+    ShiftRight,
+
+    LogicalAnd,
+    LogicalOr,
+
+    Not,
+    BitNot,
+}
 
 impl Operator {
-    pub fn from_token(token: &Token) -> Result<Self, CompileError> {
+    pub fn from_token(token: &Token, span: Span) -> Result<Self, CompileError> {
         match token {
             Token::Add => Ok(Operator::Add),
             Token::AddAssign => Ok(Operator::AddAssign),
@@ -46,20 +60,135 @@ impl Operator {
             Token::LessEqual => Ok(Operator::LessEqual),
             Token::Greater => Ok(Operator::Greater),
             Token::GreaterEqual => Ok(Operator::GreaterEqual),
-            token => Err(CompileError::new(format!(
-                "Expected operator, found {:?}",
-                token
-            ))),
+            Token::Equal => Ok(Operator::Equal),
+            Token::NotEqual => Ok(Operator::NotEqual),
+            Token::BitAnd => Ok(Operator::BitAnd),
+            Token::BitOr => Ok(Operator::BitOr),
+            Token::BitXor => Ok(Operator::BitXor),
+            Token::ShiftLeft => Ok(Operator::ShiftLeft),
+            Token::ShiftRight => Ok(Operator::ShiftRight),
+            Token::LogicalAnd => Ok(Operator::LogicalAnd),
+            Token::LogicalOr => Ok(Operator::LogicalOr),
+            Token::Not => Ok(Operator::Not),
+            Token::BitNot => Ok(Operator::BitNot),
+            token => Err(CompileError::new(
+                format!("Expected operator, found {:?}", token),
+                span,
+            )),
+        }
+    }
+
+    /// Binding power of the operator when used between two expressions, `None` if it is no binary operator.
+    /// All binary operators are left-associative, so `a - b - c` parses as `(a - b) - c`.
+    ///
+    /// | Precedence | Operators              |
+    /// |------------|------------------------|
+    /// | 10         | `*` `/` `%`            |
+    /// | 9          | `+` `-`                |
+    /// | 8          | `<<` `>>`              |
+    /// | 7          | `&`                    |
+    /// | 6          | `^`                    |
+    /// | 5          | `\|`                   |
+    /// | 4          | `<` `<=` `>` `>=`      |
+    /// | 3          | `==` `!=`              |
+    /// | 2          | `&&`                   |
+    /// | 1          | `\|\|`                 |
+    ///
+    /// Like in Rust, bitwise operators bind tighter than comparisons, so `a & 1 == 0` is `(a & 1) == 0`.
+    pub fn precedence(&self) -> Option<u8> {
+        match self {
+            Operator::Mul | Operator::Div | Operator::Mod => Some(10),
+            Operator::Add | Operator::Sub => Some(9),
+            Operator::ShiftLeft | Operator::ShiftRight => Some(8),
+            Operator::BitAnd => Some(7),
+            Operator::BitXor => Some(6),
+            Operator::BitOr => Some(5),
+            Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual => {
+                Some(4)
+            }
+            Operator::Equal | Operator::NotEqual => Some(3),
+            Operator::LogicalAnd => Some(2),
+            Operator::LogicalOr => Some(1),
+            Operator::AddAssign
+            | Operator::SubAssign
+            | Operator::MulAssign
+            | Operator::DivAssign
+            | Operator::ModAssign
+            | Operator::Not
+            | Operator::BitNot => None,
+        }
+    }
+
+    /// Binary operators whose result has the type of their operands
+    pub fn passes_type_to_operands(&self) -> bool {
+        matches!(
+            self,
+            Operator::Add
+                | Operator::Sub
+                | Operator::Mul
+                | Operator::Div
+                | Operator::Mod
+                | Operator::BitAnd
+                | Operator::BitOr
+                | Operator::BitXor
+                | Operator::ShiftLeft
+                | Operator::ShiftRight
+        )
+    }
+
+    /// Operator a compound assignment applies, `+` for `+=`
+    pub fn compound_operator(&self) -> Option<Operator> {
+        match self {
+            Operator::AddAssign => Some(Operator::Add),
+            Operator::SubAssign => Some(Operator::Sub),
+            Operator::MulAssign => Some(Operator::Mul),
+            Operator::DivAssign => Some(Operator::Div),
+            Operator::ModAssign => Some(Operator::Mod),
+            _ => None,
         }
     }
 }
 
+impl Display for Operator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::AddAssign => "+=",
+            Operator::Sub => "-",
+            Operator::SubAssign => "-=",
+            Operator::Mul => "*",
+            Operator::MulAssign => "*=",
+            Operator::Div => "/",
+            Operator::DivAssign => "/=",
+            Operator::Mod => "%",
+            Operator::ModAssign => "%=",
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterEqual => ">=",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::BitAnd => "&",
+            Operator::BitOr => "|",
+            Operator::BitXor => "^",
+            Operator::ShiftLeft => "<<",
+            Operator::ShiftRight => ">>",
+            Operator::LogicalAnd => "&&",
+            Operator::LogicalOr => "||",
+            Operator::Not => "!",
+            Operator::BitNot => "~",
+        };
+        f.write_str(symbol)
+    }
+}
+
 impl FromTokenStream for Operator {
     fn from_token_stream(tokens: &mut Tokens) -> Result<Self, CompileError>
     where
         Self: Sized,
     {
-        Self::from_token(tokens.get())
+        let token = tokens.get();
+        Self::from_token(&token.token, token.span)
     }
 }
 
@@ -70,6 +199,22 @@ pub enum Expression {
     Constant(ConstantExpression),
     Variable(VariableExpression),
     Call(CallExpression),
+    Loop(LoopStatement),
+    String(StringExpression),
+}
+
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Binary(exp) => exp.span,
+            Expression::Unary(exp) => exp.span,
+            Expression::Constant(exp) => exp.span,
+            Expression::Variable(exp) => exp.span,
+            Expression::Call(exp) => exp.span,
+            Expression::Loop(exp) => exp.span,
+            Expression::String(exp) => exp.span,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -77,23 +222,32 @@ pub struct BinaryExpression {
     pub operator: Operator,
     pub lhs: Box<Expression>,
     pub rhs: Box<Expression>,
+    pub span: Span,
+    pub operator_span: Span,
 }
 
+/// Prefix operator applied to an expression, `-` is `Operator::Sub`, `&` is `Operator::BitAnd` (address-of)
+/// and `*` is `Operator::Mul` (dereference)
 #[derive(Debug)]
 pub struct UnaryExpression {
-    operator: Operator,
-    lhs: Box<Expression>,
+    pub operator: Operator,
+    pub operand: Box<Expression>,
+    pub span: Span,
+    pub operator_span: Span,
 }
 
 #[derive(Debug)]
 pub struct ConstantExpression {
     pub value: ConstantExpressionValue,
+    /// Type written directly after the literal
+    pub suffix: Option<Type>,
+    pub span: Span,
 }
 
 #[derive(Debug)]
 pub enum ConstantExpressionValue {
-    Int(i64),
     UInt(u64),
+    Float(f64),
 }
 
 impl FromTokenStream for ConstantExpression {
@@ -101,40 +255,68 @@ impl FromTokenStream for ConstantExpression {
     where
         Self: Sized,
     {
-        match tokens.get() {
-            Token::UIntLiteral(lit) => Ok(Self {
-                value: ConstantExpressionValue::UInt(*lit),
+        let SpannedToken { token, span } = tokens.get();
+
+        match token {
+            Token::IntLiteral(IntLiteral { value, suffix }) => Ok(Self {
+                value: ConstantExpressionValue::UInt(*value),
+                suffix: suffix_type(suffix, *span),
+                span: *span,
             }),
-            Token::IntLiteral(lit) => Ok(Self {
-                value: ConstantExpressionValue::Int(*lit),
+            Token::FloatLiteral(FloatLiteral { value, suffix }) => Ok(Self {
+                value: ConstantExpressionValue::Float(*value),
+                suffix: suffix_type(suffix, *span),
+                span: *span,
             }),
-            token => Err(CompileError::new(format!(
-                "Invalid constant type: {:?}",
-                token
-            ))),
+            token => Err(CompileError::new(
+                format!("Invalid constant type: {:?}", token),
+                *span,
+            )),
         }
     }
 }
 
+/// The suffix is always at the end of the literal
+fn suffix_type(suffix: &Option<String>, span: Span) -> Option<Type> {
+    suffix.as_ref().map(|suffix| Type {
+        name: suffix.clone(),
+        is_pointer: false,
+        is_mutable: false,
+        span: Span::new(span.end - suffix.len(), span.end),
+    })
+}
+
 #[derive(Debug)]
 pub struct VariableExpression {
     pub name: String,
+    pub span: Span,
+}
+
+/// String literal, it evaluates to a pointer to the first byte of the null-terminated string
+#[derive(Debug)]
+pub struct StringExpression {
+    pub value: String,
+    pub span: Span,
 }
 
+/// `name(args)`, the callee is looked up among the functions of the file
 #[derive(Debug)]
 pub struct CallExpression {
-    name: String,
-    args: Vec<Expression>,
+    pub name: String,
+    pub name_span: Span,
+    pub args: Vec<Expression>,
+    pub span: Span,
 }
 
 impl Expression {
     fn parse_ident(tokens: &mut Tokens) -> Result<Self, CompileError> {
-        let name = tokens.get().as_ident()?.to_owned();
+        let start = tokens.peek_span();
+        let name = tokens.expect_ident()?;
 
         if *tokens.peek() != Token::LParen {
-            Ok(Self::Variable(VariableExpression { name }))
+            Ok(Self::Variable(VariableExpression { name, span: start }))
         } else {
-            tokens.get().as_lparen()?;
+            tokens.expect_lparen()?;
 
             let mut args = Vec::new();
 
@@ -145,12 +327,17 @@ impl Expression {
                     break;
                 }
 
-                tokens.get().as_comma()?;
+                tokens.expect_comma()?;
             }
 
-            tokens.get().as_rparen()?;
+            tokens.expect_rparen()?;
 
-            Ok(Self::Call(CallExpression { name, args }))
+            Ok(Self::Call(CallExpression {
+                name,
+                name_span: start,
+                args,
+                span: tokens.span_from(start),
+            }))
         }
     }
 
@@ -161,59 +348,93 @@ impl Expression {
     }
 
     fn parse_paren(tokens: &mut Tokens) -> Result<Self, CompileError> {
-        tokens.get().as_lparen()?;
+        tokens.expect_lparen()?;
         let expression = Self::parse(tokens)?;
-        tokens.get().as_rparen()?;
+        tokens.expect_rparen()?;
 
         Ok(expression)
     }
 
+    /// Prefix operators bind tighter than any binary operator, `-a * b` is `(-a) * b`
+    fn parse_unary(tokens: &mut Tokens) -> Result<Self, CompileError> {
+        if !matches!(
+            tokens.peek(),
+            Token::Sub | Token::Not | Token::BitNot | Token::BitAnd | Token::Mul
+        ) {
+            return Self::parse_primary(tokens);
+        }
+
+        let operator_span = tokens.peek_span();
+        let operator = Operator::from_token_stream(tokens)?;
+        let operand = Self::parse_unary(tokens)?;
+
+        Ok(Expression::Unary(UnaryExpression {
+            operator,
+            span: operator_span.to(operand.span()),
+            operand: Box::new(operand),
+            operator_span,
+        }))
+    }
+
     fn parse_primary(tokens: &mut Tokens) -> Result<Self, CompileError> {
         match tokens.peek() {
-            Token::Identifier(ident) => Self::parse_ident(tokens),
-            Token::UIntLiteral(_) | Token::IntLiteral(_) => Self::parse_constant(tokens),
+            Token::Identifier(_) => Self::parse_ident(tokens),
+            Token::IntLiteral(_) | Token::FloatLiteral(_) => Self::parse_constant(tokens),
             Token::LParen => Self::parse_paren(tokens),
-            token => Err(CompileError::new(format!(
-                "Expected expressions, found {:?}",
-                token
-            ))),
+            Token::StringLiteral(value) => {
+                let value = value.clone();
+                tokens.add_pos(1);
+
+                Ok(Expression::String(StringExpression {
+                    value,
+                    span: tokens.prev_span(),
+                }))
+            }
+            Token::Loop | Token::Label(_) => {
+                Ok(Expression::Loop(LoopStatement::from_token_stream(tokens)?))
+            }
+            _ => Err(tokens.unexpected("expression")),
         }
     }
 
+    /// Parses binary operators binding at least as tight as `min_precedence` following `lhs`
     fn parse_bin_op_rhs(
         tokens: &mut Tokens,
-        precedence: i32,
+        min_precedence: u8,
         mut lhs: Expression,
     ) -> Result<Self, CompileError> {
         loop {
-            if *tokens.peek() == Token::Semicolon {
+            let Some(precedence) = binary_precedence(tokens.peek()) else {
                 return Ok(lhs);
-            }
-
-            let token_precedance = get_token_precedence(tokens.peek());
-            if token_precedance < precedence {
+            };
+            if precedence < min_precedence {
                 return Ok(lhs);
             }
 
+            let operator_span = tokens.peek_span();
             let operator = Operator::from_token_stream(tokens)?;
-            let mut rhs = Self::parse_primary(tokens)?;
+            let mut rhs = Self::parse_unary(tokens)?;
 
-            let next_precedance = get_token_precedence(tokens.peek());
-            if token_precedance < next_precedance {
-                rhs = Self::parse_bin_op_rhs(tokens, token_precedance + 1, rhs)?;
+            //Operators binding tighter belong to the right hand side, operators of the same precedence are
+            //picked up by the next iteration which makes them left-associative
+            if binary_precedence(tokens.peek()).is_some_and(|next| next > precedence) {
+                rhs = Self::parse_bin_op_rhs(tokens, precedence + 1, rhs)?;
             }
 
+            let span = lhs.span().to(rhs.span());
             lhs = Expression::Binary(BinaryExpression {
                 operator,
                 lhs: Box::new(lhs),
                 rhs: Box::new(rhs),
+                span,
+                operator_span,
             })
         }
     }
 
     fn parse(tokens: &mut Tokens) -> Result<Self, CompileError> {
-        let lhs = Self::parse_primary(tokens)?;
-        Self::parse_bin_op_rhs(tokens, 0, lhs)
+        let lhs = Self::parse_unary(tokens)?;
+        Self::parse_bin_op_rhs(tokens, 1, lhs)
     }
 }
 
@@ -226,28 +447,8 @@ impl FromTokenStream for Expression {
     }
 }
 
-lazy_static! {
-    static ref BINOP_PRECEDENCE: HashMap<Operator, i32> = {
-        let mut map = HashMap::new();
-
-        map.insert(Operator::Less, 10);
-        map.insert(Operator::Add, 20);
-        map.insert(Operator::Sub, 30);
-        map.insert(Operator::Mul, 40);
-
-        map
-    };
-}
-
-fn get_token_precedence(token: &Token) -> i32 {
-    let Ok(operator) = Operator::from_token(token) else {
-        return -1;
-    };
-
-    let token_precedence = BINOP_PRECEDENCE[&operator];
-    if token_precedence <= 0 {
-        -1
-    } else {
-        token_precedence
-    }
+fn binary_precedence(token: &Token) -> Option<u8> {
+    Operator::from_token(token, Span::default())
+        .ok()
+        .and_then(|operator| operator.precedence())
 }
@@ -1,35 +1,71 @@
-use crate::parser::scope::Scope;
 use crate::parser::*;
 
 #[derive(Debug)]
 pub struct Function {
     pub name: String,
+    pub name_span: Span,
+    /// Lines of the `///` comments in front of the function
+    pub doc: Vec<String>,
     pub return_type: Type,
 
     pub params: Vec<FunctionParam>,
+    /// Whether the parameter list ends with `...`, only allowed for extern functions
+    pub variadic: bool,
+    /// Declared with `extern "C"`, which means it is defined outside of the program and has no body
+    pub is_extern: bool,
 
     pub body: Option<Scope>,
+    pub span: Span,
 }
 
 impl FromTokenStream for Function {
     fn from_token_stream(tokens: &mut Tokens) -> Result<Self, CompileError> {
-        tokens.add_pos(1);
+        let doc = parse_doc(tokens)?;
+
+        let start = tokens.peek_span();
+        let is_extern = parse_extern(tokens)?;
+        tokens.expect_fun()?;
 
         let mut params = Vec::new();
+        let mut variadic = false;
         let mut return_type = Type::void();
 
         //Parse ident
-        let name = tokens.get().as_ident()?.to_owned();
+        let name = tokens.expect_ident()?;
+        let name_span = tokens.prev_span();
+        return_type.span = name_span;
 
         //Expect lparen
-        tokens.get().as_lparen()?;
+        tokens.expect_lparen()?;
 
         //Expect parameter list
         while *tokens.peek() != Token::RParen {
+            if *tokens.peek() == Token::Ellipsis {
+                let span = tokens.peek_span();
+                tokens.add_pos(1);
+
+                if *tokens.peek() != Token::RParen {
+                    return Err(tokens.unexpected("`)` after `...`"));
+                }
+                if !is_extern {
+                    return Err(CompileError::new(
+                        "Only extern functions can take a variable number of arguments".to_owned(),
+                        span,
+                    ));
+                }
+
+                variadic = true;
+                break;
+            }
+
             params.push(FunctionParam::from_token_stream(tokens)?);
+
+            if *tokens.peek() != Token::RParen {
+                tokens.expect_comma()?;
+            }
         }
 
-        tokens.get().as_rparen()?;
+        tokens.expect_rparen()?;
 
         //Parse optional return type
         if *tokens.peek() == Token::DoubleColon {
@@ -37,26 +73,90 @@ impl FromTokenStream for Function {
             return_type = Type::from_token_stream(tokens)?;
         }
 
-        let mut body = None;
-
-        //We check if function has body
-        if *tokens.peek() == Token::LBrace {
-            body = Some(Scope::from_token_stream(tokens)?);
-        }
+        //Extern functions are only declared, everything else needs a body
+        let body = if is_extern {
+            if *tokens.peek() == Token::LBrace {
+                return Err(CompileError::new(
+                    "Extern function cannot have a body".to_owned(),
+                    tokens.peek_span(),
+                ));
+            }
+            tokens.expect_semicolon()?;
+            None
+        } else if *tokens.peek() == Token::Semicolon {
+            return Err(CompileError::new(
+                "Function without a body has to be declared with `extern \"C\"`".to_owned(),
+                tokens.peek_span(),
+            ));
+        } else {
+            Some(Scope::from_token_stream(tokens)?)
+        };
 
         Ok(Self {
             name,
+            name_span,
+            doc,
             return_type,
             params,
+            variadic,
+            is_extern,
             body,
+            span: tokens.span_from(start),
         })
     }
 }
 
+/// Collects the doc comments in front of a function
+fn parse_doc(tokens: &mut Tokens) -> Result<Vec<String>, CompileError> {
+    let start = tokens.peek_span();
+    let mut doc = Vec::new();
+
+    while let Token::DocComment(line) = tokens.peek() {
+        doc.push(line.clone());
+        tokens.add_pos(1);
+    }
+
+    if !doc.is_empty() && !matches!(tokens.peek(), Token::Fun | Token::Extern) {
+        return Err(CompileError::new(
+            "Doc comment is not followed by a function".to_owned(),
+            tokens.span_from(start),
+        ));
+    }
+
+    Ok(doc)
+}
+
+/// Parses an optional `extern "C"`, the only supported ABI is the one of C
+fn parse_extern(tokens: &mut Tokens) -> Result<bool, CompileError> {
+    if *tokens.peek() != Token::Extern {
+        return Ok(false);
+    }
+    tokens.add_pos(1);
+
+    match tokens.peek() {
+        Token::StringLiteral(abi) if abi == "C" => {
+            tokens.add_pos(1);
+            Ok(true)
+        }
+        //The rest of the declaration is still valid, so parsing continues as if it were "C"
+        Token::StringLiteral(abi) => {
+            let err = CompileError::new(
+                format!("Unsupported ABI \"{}\", only \"C\" is supported", abi),
+                tokens.peek_span(),
+            );
+            tokens.report(err);
+            tokens.add_pos(1);
+            Ok(true)
+        }
+        _ => Err(tokens.unexpected("ABI string like \"C\"")),
+    }
+}
+
 #[derive(Debug)]
 pub struct FunctionParam {
     pub name: String,
     pub r#type: Type,
+    pub span: Span,
 }
 
 impl FromTokenStream for FunctionParam {
@@ -64,15 +164,14 @@ impl FromTokenStream for FunctionParam {
     where
         Self: Sized,
     {
-        let name = tokens.get().as_ident()?.to_owned();
-        tokens.get().as_double_colon()?;
+        let start = tokens.peek_span();
 
-        let mut r#type = Type::from_token_stream(tokens)?;
+        let name = tokens.expect_ident()?;
+        tokens.expect_double_colon()?;
 
-        if *tokens.try_peek()? == Token::Comma {
-            tokens.add_pos(1);
-        }
+        let r#type = Type::from_token_stream(tokens)?;
+        let span = tokens.span_from(start);
 
-        Ok(Self { name, r#type })
+        Ok(Self { name, r#type, span })
     }
 }
@@ -0,0 +1,49 @@
+use crate::lexer::{Token, Tokens};
+use crate::parser::expression::Expression;
+use crate::parser::scope::Scope;
+use crate::parser::{CompileError, FromTokenStream};
+use crate::span::Span;
+
+/// `if condition { ... }`, optionally followed by `else if` chains and a final `else { ... }`
+#[derive(Debug)]
+pub struct IfStatement {
+    pub condition: Expression,
+    pub then_block: Scope,
+    pub else_branch: Option<ElseBranch>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum ElseBranch {
+    If(Box<IfStatement>),
+    Block(Scope),
+}
+
+impl FromTokenStream for IfStatement {
+    fn from_token_stream(tokens: &mut Tokens) -> Result<Self, CompileError>
+    where
+        Self: Sized,
+    {
+        let start = tokens.expect_if()?;
+        let condition = Expression::from_token_stream(tokens)?;
+        let then_block = Scope::from_token_stream(tokens)?;
+
+        let mut else_branch = None;
+        if *tokens.peek() == Token::Else {
+            tokens.add_pos(1);
+
+            else_branch = Some(match tokens.peek() {
+                Token::If => ElseBranch::If(Box::new(IfStatement::from_token_stream(tokens)?)),
+                Token::LBrace => ElseBranch::Block(Scope::from_token_stream(tokens)?),
+                _ => return Err(tokens.unexpected("`if` or block after `else`")),
+            });
+        }
+
+        Ok(Self {
+            condition,
+            then_block,
+            else_branch,
+            span: tokens.span_from(start),
+        })
+    }
+}
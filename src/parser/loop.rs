@@ -0,0 +1,78 @@
+use crate::lexer::{Token, Tokens};
+use crate::parser::scope::Scope;
+use crate::parser::{CompileError, FromTokenStream};
+use crate::span::Span;
+
+/// `loop { ... }`, repeats until it is left with `break` or `return`. Used as an expression its value is the
+/// value passed to `break`.
+#[derive(Debug)]
+pub struct LoopStatement {
+    pub label: Option<Label>,
+    pub body: Scope,
+    pub span: Span,
+}
+
+impl LoopStatement {
+    /// Parses the loop after its label, if it has one
+    pub fn parse(tokens: &mut Tokens, label: Option<Label>) -> Result<Self, CompileError> {
+        let loop_span = tokens.expect_loop()?;
+        let start = label.as_ref().map_or(loop_span, |label| label.span);
+        let body = Scope::from_token_stream(tokens)?;
+
+        Ok(Self {
+            label,
+            body,
+            span: tokens.span_from(start),
+        })
+    }
+}
+
+impl FromTokenStream for LoopStatement {
+    fn from_token_stream(tokens: &mut Tokens) -> Result<Self, CompileError>
+    where
+        Self: Sized,
+    {
+        let label = match tokens.peek() {
+            Token::Label(_) => Some(Label::declaration(tokens)?),
+            _ => None,
+        };
+
+        Self::parse(tokens, label)
+    }
+}
+
+/// Name of a loop like `'outer`, the name is stored without the leading `'`
+#[derive(Debug)]
+pub struct Label {
+    pub name: String,
+    pub span: Span,
+}
+
+impl Label {
+    /// Parses a label in front of a loop, which is followed by a colon
+    pub fn declaration(tokens: &mut Tokens) -> Result<Self, CompileError> {
+        let label = Self::from_token_stream(tokens)?;
+        tokens.expect_double_colon()?;
+
+        Ok(label)
+    }
+}
+
+impl FromTokenStream for Label {
+    fn from_token_stream(tokens: &mut Tokens) -> Result<Self, CompileError>
+    where
+        Self: Sized,
+    {
+        let Token::Label(name) = tokens.peek() else {
+            return Err(tokens.unexpected("label"));
+        };
+
+        let label = Self {
+            name: name.clone(),
+            span: tokens.peek_span(),
+        };
+        tokens.add_pos(1);
+
+        Ok(label)
+    }
+}
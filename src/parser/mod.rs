@@ -1,28 +1,51 @@
+use crate::diagnostic::Diagnostic;
 use crate::lexer::{Token, Tokens};
+use crate::span::Span;
 use std::ops::{Deref, DerefMut};
+use thiserror::Error;
 
 mod assignment;
 mod expression;
 mod function;
+mod r#if;
+mod r#loop;
+mod reassignment;
 mod scope;
 mod statement;
 mod r#type;
+mod r#while;
 
 pub use assignment::*;
 pub use expression::*;
 pub use function::*;
+pub use r#if::*;
+pub use r#loop::*;
 pub use r#type::*;
+pub use r#while::*;
+pub use reassignment::*;
 pub use scope::*;
-pub use statement::Statement;
+pub use statement::*;
 
-#[derive(Debug)]
+#[derive(Debug, Error)]
+#[error("{message}")]
 pub struct CompileError {
     message: String,
+    span: Span,
 }
 
 impl CompileError {
-    pub fn new(message: String) -> Self {
-        Self { message }
+    pub fn new(message: String, span: Span) -> Self {
+        Self { message, span }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl From<&CompileError> for Diagnostic {
+    fn from(err: &CompileError) -> Self {
+        Diagnostic::error(err.message.clone()).with_label(err.span, "")
     }
 }
 
@@ -34,9 +57,7 @@ pub trait FromTokenStream {
 
 #[derive(Debug)]
 pub enum Element {
-    SourceFile(Vec<Element>),
     Function(Function),
-    Statement(Statement),
 }
 
 #[derive(Debug, Default)]
@@ -56,46 +77,71 @@ impl DerefMut for SourceFile {
     }
 }
 
-pub enum ParsingState {
-    Idle,
+pub struct Parser<'a> {
+    tokens: &'a mut Tokens,
 }
 
-pub struct Parser {
-    root: SourceFile,
-}
+impl<'a> Parser<'a> {
+    pub fn new(tokens: &'a mut Tokens) -> Self {
+        Self { tokens }
+    }
 
-impl Parser {
-    pub fn new(tokens: &mut Tokens) -> Self {
+    /// Parses the whole token stream, errors are recovered from so that all of them are reported at once
+    pub fn parse(self) -> Result<SourceFile, Vec<Diagnostic>> {
+        let tokens = self.tokens;
         let mut root = SourceFile::default();
-        let mut parsing_state = ParsingState::Idle;
 
         while tokens.has_more() {
-            let token = tokens.peek();
-            match token {
-                Token::Fun => {
-                    let function = Function::from_token_stream(tokens).unwrap(); //TODO:
-                    (*root).push(Element::Function(function));
+            match tokens.peek() {
+                Token::Fun | Token::Extern | Token::DocComment(_) => {
+                    match Function::from_token_stream(tokens) {
+                        Ok(function) => (*root).push(Element::Function(function)),
+                        Err(err) => {
+                            tokens.report(err);
+                            skip_to_function(tokens);
+                        }
+                    }
                 }
-                token => {
-                    println!("Suspicious token : {:?}", token);
+                _ => {
+                    tokens.report(tokens.unexpected("function"));
+                    tokens.add_pos(1);
+                    skip_to_function(tokens);
                 }
             }
         }
 
-        Self { root }
-    }
-
-    pub fn print_ast(&self) {
-        println!("{:?}", &self.root);
+        let diagnostics = tokens.take_diagnostics();
+        if diagnostics.is_empty() {
+            Ok(root)
+        } else {
+            Err(diagnostics)
+        }
     }
+}
 
-    pub fn root(&self) -> &SourceFile {
-        &self.root
+/// Skips tokens until the start of the next function or its doc comment
+fn skip_to_function(tokens: &mut Tokens) {
+    while tokens.has_more()
+        && !matches!(
+            tokens.peek(),
+            Token::Fun | Token::Extern | Token::DocComment(_)
+        )
+    {
+        tokens.add_pos(1);
     }
 }
 
-#[derive(Debug)]
-pub enum LiteralType {
-    UInt(u64),
-    Int(i64),
+/// Skips tokens until the end of the current statement, a `;` is consumed while `}`, `fun` and `extern` are
+/// left for the enclosing scope and function to deal with
+pub(crate) fn skip_to_statement_end(tokens: &mut Tokens) {
+    while tokens.has_more() {
+        match tokens.peek() {
+            Token::Semicolon => {
+                tokens.add_pos(1);
+                return;
+            }
+            Token::RBrace | Token::Fun | Token::Extern => return,
+            _ => tokens.add_pos(1),
+        }
+    }
 }
@@ -0,0 +1,59 @@
+use crate::lexer::{Token, Tokens};
+use crate::parser::expression::{Expression, Operator};
+use crate::parser::{CompileError, FromTokenStream};
+use crate::span::Span;
+
+/// `target = value;` or a compound assignment like `target += value;`, the target has to be a variable or a
+/// dereferenced pointer
+#[derive(Debug)]
+pub struct ReassignmentStatement {
+    pub target: Expression,
+    /// `None` for `=`, otherwise the compound assignment operator, e.g. `Operator::AddAssign` for `+=`
+    pub operator: Option<Operator>,
+    pub operator_span: Span,
+    pub value: Expression,
+    pub span: Span,
+}
+
+impl FromTokenStream for ReassignmentStatement {
+    fn from_token_stream(tokens: &mut Tokens) -> Result<Self, CompileError>
+    where
+        Self: Sized,
+    {
+        let target = Expression::from_token_stream(tokens)?;
+
+        Self::parse(tokens, target)
+    }
+}
+
+impl ReassignmentStatement {
+    /// Parses the rest of the statement after its already parsed `target`
+    pub fn parse(tokens: &mut Tokens, target: Expression) -> Result<Self, CompileError> {
+        let start = target.span();
+
+        let operator_span = tokens.peek_span();
+        let operator = match tokens.peek() {
+            Token::Assign => None,
+            token @ (Token::AddAssign
+            | Token::SubAssign
+            | Token::MulAssign
+            | Token::DivAssign
+            | Token::ModAssign) => Some(Operator::from_token(token, operator_span)?),
+            _ => {
+                return Err(tokens.unexpected("`;`, `=` or a compound assignment operator"));
+            }
+        };
+        tokens.add_pos(1);
+
+        let value = Expression::from_token_stream(tokens)?;
+        tokens.expect_semicolon()?;
+
+        Ok(Self {
+            target,
+            operator,
+            operator_span,
+            value,
+            span: tokens.span_from(start),
+        })
+    }
+}
@@ -1,10 +1,12 @@
 use crate::lexer::{Token, Tokens};
 use crate::parser::statement::Statement;
-use crate::parser::{CompileError, Element, FromTokenStream};
+use crate::parser::{skip_to_statement_end, CompileError, FromTokenStream};
+use crate::span::Span;
 
 #[derive(Debug)]
 pub struct Scope {
     pub elements: Vec<Statement>,
+    pub span: Span,
 }
 
 impl FromTokenStream for Scope {
@@ -12,21 +14,37 @@ impl FromTokenStream for Scope {
     where
         Self: Sized,
     {
-        tokens.get().as_lbrace()?;
+        let start = tokens.peek_span();
+        tokens.expect_lbrace()?;
 
         let mut elements = Vec::new();
 
-        while tokens.has_more() && *tokens.peek() != Token::RBrace {
-            //We parse assignment
-            elements.push(Statement::from_token_stream(tokens)?);
-        }
+        while tokens.has_more()
+            && !matches!(tokens.peek(), Token::RBrace | Token::Fun | Token::Extern)
+        {
+            if let Token::DocComment(_) = tokens.peek() {
+                tokens.report(CompileError::new(
+                    "Doc comments can only document functions".to_owned(),
+                    tokens.peek_span(),
+                ));
+                tokens.add_pos(1);
+                continue;
+            }
 
-        while *tokens.peek() != Token::RBrace {
-            tokens.add_pos(1);
+            match Statement::from_token_stream(tokens) {
+                Ok(statement) => elements.push(statement),
+                Err(err) => {
+                    tokens.report(err);
+                    skip_to_statement_end(tokens);
+                }
+            }
         }
 
-        tokens.get().as_rbrace()?;
+        tokens.expect_rbrace()?;
 
-        Ok(Self { elements })
+        Ok(Self {
+            elements,
+            span: tokens.span_from(start),
+        })
     }
 }
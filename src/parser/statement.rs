@@ -1,17 +1,51 @@
 use crate::lexer::{Token, Tokens};
 use crate::parser::assignment::AssignmentStatement;
 use crate::parser::expression::Expression;
+use crate::parser::r#if::IfStatement;
+use crate::parser::r#loop::{Label, LoopStatement};
+use crate::parser::r#while::WhileStatement;
+use crate::parser::reassignment::ReassignmentStatement;
+use crate::parser::scope::Scope;
 use crate::parser::{CompileError, FromTokenStream};
+use crate::span::Span;
 
 #[derive(Debug)]
 pub enum Statement {
     Return(ReturnStatement),
     Declaration(AssignmentStatement),
+    /// Nested `{ ... }`, variables declared inside go out of scope at its end
+    Block(Scope),
+    Reassignment(ReassignmentStatement),
+    If(IfStatement),
+    While(WhileStatement),
+    Loop(LoopStatement),
+    Break(BreakStatement),
+    Continue(ContinueStatement),
+    /// Expression evaluated for its side effects like `foo(1);`
+    Expression(ExpressionStatement),
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Return(stat) => stat.span,
+            Statement::Declaration(stat) => stat.span,
+            Statement::Block(stat) => stat.span,
+            Statement::Reassignment(stat) => stat.span,
+            Statement::If(stat) => stat.span,
+            Statement::While(stat) => stat.span,
+            Statement::Loop(stat) => stat.span,
+            Statement::Break(stat) => stat.span,
+            Statement::Continue(stat) => stat.span,
+            Statement::Expression(stat) => stat.span,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct ReturnStatement {
-    expression: Option<Expression>,
+    pub expression: Option<Expression>,
+    pub span: Span,
 }
 
 impl FromTokenStream for ReturnStatement {
@@ -19,15 +53,88 @@ impl FromTokenStream for ReturnStatement {
     where
         Self: Sized,
     {
-        tokens.get().as_return()?;
+        let start = tokens.peek_span();
+        tokens.expect_return()?;
         let mut expression = None;
 
         if *tokens.peek() != Token::Semicolon {
             expression = Some(Expression::from_token_stream(tokens)?);
         }
 
-        tokens.get().as_semicolon()?;
-        Ok(Self { expression })
+        tokens.expect_semicolon()?;
+        Ok(Self {
+            expression,
+            span: tokens.span_from(start),
+        })
+    }
+}
+
+/// `break 'label value;`, leaves the labeled loop or the innermost loop if there is no label. The value
+/// becomes the value of a `loop` expression.
+#[derive(Debug)]
+pub struct BreakStatement {
+    pub label: Option<Label>,
+    pub value: Option<Expression>,
+    pub span: Span,
+}
+
+impl FromTokenStream for BreakStatement {
+    fn from_token_stream(tokens: &mut Tokens) -> Result<Self, CompileError>
+    where
+        Self: Sized,
+    {
+        let start = tokens.expect_break()?;
+        let label = optional_label(tokens)?;
+
+        let mut value = None;
+        if *tokens.peek() != Token::Semicolon {
+            value = Some(Expression::from_token_stream(tokens)?);
+        }
+
+        tokens.expect_semicolon()?;
+
+        Ok(Self {
+            label,
+            value,
+            span: tokens.span_from(start),
+        })
+    }
+}
+
+/// `continue 'label;`, starts the next iteration of the labeled loop or the innermost loop if there is no
+/// label
+#[derive(Debug)]
+pub struct ContinueStatement {
+    pub label: Option<Label>,
+    pub span: Span,
+}
+
+impl FromTokenStream for ContinueStatement {
+    fn from_token_stream(tokens: &mut Tokens) -> Result<Self, CompileError>
+    where
+        Self: Sized,
+    {
+        let start = tokens.expect_continue()?;
+        let label = optional_label(tokens)?;
+        tokens.expect_semicolon()?;
+
+        Ok(Self {
+            label,
+            span: tokens.span_from(start),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ExpressionStatement {
+    pub expression: Expression,
+    pub span: Span,
+}
+
+fn optional_label(tokens: &mut Tokens) -> Result<Option<Label>, CompileError> {
+    match tokens.peek() {
+        Token::Label(_) => Ok(Some(Label::from_token_stream(tokens)?)),
+        _ => Ok(None),
     }
 }
 
@@ -43,10 +150,44 @@ impl FromTokenStream for Statement {
             Token::Return => Ok(Statement::Return(ReturnStatement::from_token_stream(
                 tokens,
             )?)),
-            token => Err(CompileError::new(format!(
-                "Expected statement, found {:?}",
-                token
-            ))),
+            Token::While => Ok(Statement::While(WhileStatement::from_token_stream(tokens)?)),
+            Token::Loop => Ok(Statement::Loop(LoopStatement::from_token_stream(tokens)?)),
+            Token::Label(_) => {
+                let label = Label::declaration(tokens)?;
+
+                match tokens.peek() {
+                    Token::Loop => Ok(Statement::Loop(LoopStatement::parse(tokens, Some(label))?)),
+                    Token::While => Ok(Statement::While(WhileStatement::parse(
+                        tokens,
+                        Some(label),
+                    )?)),
+                    _ => Err(tokens.unexpected("`loop` or `while` after a label")),
+                }
+            }
+            Token::Break => Ok(Statement::Break(BreakStatement::from_token_stream(tokens)?)),
+            Token::Continue => Ok(Statement::Continue(ContinueStatement::from_token_stream(
+                tokens,
+            )?)),
+            Token::If => Ok(Statement::If(IfStatement::from_token_stream(tokens)?)),
+            Token::LBrace => Ok(Statement::Block(Scope::from_token_stream(tokens)?)),
+            //Both start with an expression, only an assignment continues after it
+            _ => {
+                let start = tokens.peek_span();
+                let expression = Expression::from_token_stream(tokens)?;
+
+                if *tokens.peek() == Token::Semicolon {
+                    tokens.add_pos(1);
+
+                    Ok(Statement::Expression(ExpressionStatement {
+                        expression,
+                        span: tokens.span_from(start),
+                    }))
+                } else {
+                    Ok(Statement::Reassignment(ReassignmentStatement::parse(
+                        tokens, expression,
+                    )?))
+                }
+            }
         }
     }
 }
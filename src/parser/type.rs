@@ -4,6 +4,9 @@ use crate::parser::*;
 pub struct Type {
     pub name: String,
     pub is_pointer: bool,
+    /// `*var T`, the value behind the pointer can be assigned to
+    pub is_mutable: bool,
+    pub span: Span,
 }
 
 impl Type {
@@ -11,6 +14,8 @@ impl Type {
         Self {
             name: "()".to_owned(),
             is_pointer: false,
+            is_mutable: false,
+            span: Span::default(),
         }
     }
 }
@@ -20,16 +25,28 @@ impl FromTokenStream for Type {
     where
         Self: Sized,
     {
+        let start = tokens.peek_span();
         let mut is_pointer = false;
+        let mut is_mutable = false;
 
         //TODO: we have to parse things like double pointers etc.
         if *tokens.peek() == Token::Mul {
             tokens.add_pos(1);
             is_pointer = true;
+
+            if *tokens.peek() == Token::Var {
+                tokens.add_pos(1);
+                is_mutable = true;
+            }
         }
 
-        let name = tokens.get().as_ident()?.to_owned();
+        let name = tokens.expect_ident()?;
 
-        Ok(Self { name, is_pointer })
+        Ok(Self {
+            name,
+            is_pointer,
+            is_mutable,
+            span: tokens.span_from(start),
+        })
     }
 }
@@ -0,0 +1,41 @@
+use crate::lexer::Tokens;
+use crate::parser::expression::Expression;
+use crate::parser::r#loop::Label;
+use crate::parser::scope::Scope;
+use crate::parser::{CompileError, FromTokenStream};
+use crate::span::Span;
+
+/// `while condition { ... }`, the condition is checked before every iteration
+#[derive(Debug)]
+pub struct WhileStatement {
+    pub label: Option<Label>,
+    pub condition: Expression,
+    pub body: Scope,
+    pub span: Span,
+}
+
+impl WhileStatement {
+    /// Parses the loop after its label, if it has one
+    pub fn parse(tokens: &mut Tokens, label: Option<Label>) -> Result<Self, CompileError> {
+        let while_span = tokens.expect_while()?;
+        let start = label.as_ref().map_or(while_span, |label| label.span);
+        let condition = Expression::from_token_stream(tokens)?;
+        let body = Scope::from_token_stream(tokens)?;
+
+        Ok(Self {
+            label,
+            condition,
+            body,
+            span: tokens.span_from(start),
+        })
+    }
+}
+
+impl FromTokenStream for WhileStatement {
+    fn from_token_stream(tokens: &mut Tokens) -> Result<Self, CompileError>
+    where
+        Self: Sized,
+    {
+        Self::parse(tokens, None)
+    }
+}
@@ -0,0 +1,420 @@
+use crate::parser::{
+    BinaryExpression, CallExpression, ConstantExpression, ConstantExpressionValue, Expression,
+    Operator, UnaryExpression, VariableExpression,
+};
+use crate::sema::hir::{self, ExpressionKind, Place};
+use crate::sema::{resolve_type, FunctionScope, SemaError, Type};
+use crate::span::Span;
+
+impl Expression {
+    /// Resolves and types the expression, literals without a suffix take the `expected` type if it fits them
+    pub fn analyze(
+        &self,
+        scope: &mut FunctionScope,
+        expected: Option<&Type>,
+    ) -> Result<hir::Expression, SemaError> {
+        match self {
+            Expression::Binary(exp) => exp.analyze(scope, expected),
+            Expression::Unary(exp) => exp.analyze(scope, expected),
+            Expression::Constant(exp) => exp.analyze(expected, None),
+            Expression::Variable(exp) => exp.analyze(scope, expected),
+            Expression::Call(exp) => exp.analyze(scope),
+            Expression::String(exp) => Ok(hir::Expression {
+                kind: ExpressionKind::String(exp.value.clone()),
+                r#type: Type::Int {
+                    signed: false,
+                    bits: 8,
+                }
+                .pointer_to(false),
+            }),
+            Expression::Loop(exp) => {
+                let value = exp.analyze(scope, expected)?;
+
+                Ok(hir::Expression {
+                    r#type: value.r#type.clone(),
+                    kind: ExpressionKind::Loop(value),
+                })
+            }
+        }
+    }
+
+    /// Whether the type of the expression is decided by its context, which is the case for literals without
+    /// a suffix, locals declared with `let` from such literals and arithmetic on them
+    pub fn is_untyped(&self, scope: &FunctionScope) -> bool {
+        match self {
+            Expression::Constant(exp) => exp.suffix.is_none(),
+            Expression::Unary(exp) => {
+                matches!(exp.operator, Operator::Sub | Operator::BitNot)
+                    && exp.operand.is_untyped(scope)
+            }
+            Expression::Binary(exp) => {
+                exp.operator.passes_type_to_operands()
+                    && exp.lhs.is_untyped(scope)
+                    && exp.rhs.is_untyped(scope)
+            }
+            Expression::Variable(exp) => scope
+                .lookup(&exp.name, exp.span)
+                .is_ok_and(|local| scope.untyped_declaration(local).is_some()),
+            Expression::Call(_) | Expression::Loop(_) | Expression::String(_) => false,
+        }
+    }
+
+    /// Whether the expression denotes a memory location that can be assigned to or borrowed
+    pub fn is_place(&self) -> bool {
+        match self {
+            Expression::Variable(_) => true,
+            Expression::Unary(unary) => unary.operator == Operator::Mul,
+            _ => false,
+        }
+    }
+
+    /// Resolves a place expression, which is either a variable or a dereferenced pointer. Returns the place
+    /// and the type of the value stored there.
+    pub fn analyze_place(&self, scope: &mut FunctionScope) -> Result<(Place, Type), SemaError> {
+        match self {
+            Expression::Variable(variable) => {
+                let local = scope.lookup(&variable.name, variable.span)?;
+
+                Ok((Place::Local(local), scope.local(local).r#type.clone()))
+            }
+            Expression::Unary(unary) if unary.operator == Operator::Mul => {
+                let pointer = unary.operand.analyze(scope, None)?;
+
+                match pointer.r#type.pointee().cloned() {
+                    Some(r#type) => Ok((Place::Deref(Box::new(pointer)), r#type)),
+                    None => Err(unary.invalid_operand(&pointer.r#type)),
+                }
+            }
+            _ => Err(SemaError::AddressOfTemporary(self.span())),
+        }
+    }
+}
+
+impl BinaryExpression {
+    pub fn analyze(
+        &self,
+        scope: &mut FunctionScope,
+        expected: Option<&Type>,
+    ) -> Result<hir::Expression, SemaError> {
+        if matches!(self.operator, Operator::LogicalAnd | Operator::LogicalOr) {
+            let lhs = self.lhs.analyze(scope, None)?;
+            if !lhs.r#type.is_bool() {
+                return Err(self.invalid_operand(&lhs, &self.lhs));
+            }
+
+            let rhs = self.rhs.analyze(scope, None)?;
+            if !rhs.r#type.is_bool() {
+                return Err(self.invalid_operand(&rhs, &self.rhs));
+            }
+
+            return Ok(self.typed(lhs, rhs, Type::Bool));
+        }
+
+        //Comparisons produce a `bool` no matter what their operands are
+        let expected = expected.filter(|_| self.operator.passes_type_to_operands());
+
+        //An untyped left hand side takes the type of the right hand side
+        let (lhs, rhs) = if self.lhs.is_untyped(scope) && !self.rhs.is_untyped(scope) {
+            let rhs = self.rhs.analyze(scope, expected)?;
+            let lhs = self.lhs.analyze(scope, Some(&rhs.r#type))?;
+            (lhs, rhs)
+        } else {
+            let lhs = self.lhs.analyze(scope, expected)?;
+            let rhs = self.rhs.analyze(scope, Some(&lhs.r#type))?;
+            (lhs, rhs)
+        };
+
+        if lhs.r#type != rhs.r#type {
+            return Err(SemaError::TypesNotEqual {
+                lhs: lhs.r#type.to_string(),
+                lhs_span: self.lhs.span(),
+                rhs: rhs.r#type.to_string(),
+                rhs_span: self.rhs.span(),
+                span: self.operator_span,
+            });
+        }
+
+        if !supports(self.operator, &lhs.r#type) {
+            return Err(self.invalid_operand(&lhs, &self.lhs));
+        }
+
+        let r#type = if self.operator.passes_type_to_operands() {
+            lhs.r#type.clone()
+        } else {
+            Type::Bool
+        };
+
+        Ok(self.typed(lhs, rhs, r#type))
+    }
+
+    fn typed(&self, lhs: hir::Expression, rhs: hir::Expression, r#type: Type) -> hir::Expression {
+        hir::Expression {
+            kind: ExpressionKind::Binary(hir::Binary {
+                operator: self.operator,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            }),
+            r#type,
+        }
+    }
+
+    fn invalid_operand(&self, operand: &hir::Expression, expression: &Expression) -> SemaError {
+        SemaError::InvalidOperand {
+            operator: self.operator.to_string(),
+            r#type: operand.r#type.to_string(),
+            operand_span: expression.span(),
+            span: self.operator_span,
+        }
+    }
+}
+
+/// Whether the binary `operator` can be applied to two operands of type `r#type`
+pub fn supports(operator: Operator, r#type: &Type) -> bool {
+    let numeric = r#type.is_integer() || r#type.is_float();
+
+    match operator {
+        Operator::Equal | Operator::NotEqual => numeric || r#type.is_bool() || r#type.is_pointer(),
+        Operator::Add
+        | Operator::Sub
+        | Operator::Mul
+        | Operator::Div
+        | Operator::Mod
+        | Operator::Less
+        | Operator::LessEqual
+        | Operator::Greater
+        | Operator::GreaterEqual => numeric,
+        _ => r#type.is_integer(),
+    }
+}
+
+impl UnaryExpression {
+    pub fn analyze(
+        &self,
+        scope: &mut FunctionScope,
+        expected: Option<&Type>,
+    ) -> Result<hir::Expression, SemaError> {
+        //The operand of `&` is not evaluated, only its address is needed
+        if self.operator == Operator::BitAnd {
+            let (place, r#type) = self.operand.analyze_place(scope)?;
+            let mutable = scope.is_mutable(&place);
+
+            return Ok(hir::Expression {
+                kind: ExpressionKind::AddressOf(place),
+                r#type: r#type.pointer_to(mutable),
+            });
+        }
+
+        let operand = match (&self.operator, &*self.operand) {
+            //The sign is part of the literal, so `-128i8` fits into its type
+            (Operator::Sub, Expression::Constant(constant)) => {
+                return constant.analyze(expected, Some(self.span))
+            }
+            (Operator::Sub | Operator::BitNot, operand) => operand.analyze(scope, expected)?,
+            (_, operand) => operand.analyze(scope, None)?,
+        };
+
+        let supported = match self.operator {
+            //Negating an unsigned value always wraps around
+            Operator::Sub => operand.r#type.is_signed() || operand.r#type.is_float(),
+            Operator::BitNot => operand.r#type.is_integer(),
+            Operator::Not => operand.r#type.is_bool(),
+            Operator::Mul => operand.r#type.is_pointer(),
+            _ => false,
+        };
+
+        if !supported {
+            return Err(self.invalid_operand(&operand.r#type));
+        }
+
+        if self.operator == Operator::Mul {
+            let r#type = operand.r#type.pointee().cloned().unwrap_or(Type::Void);
+
+            return Ok(hir::Expression {
+                kind: ExpressionKind::Deref(Box::new(operand)),
+                r#type,
+            });
+        }
+
+        Ok(hir::Expression {
+            r#type: operand.r#type.clone(),
+            kind: ExpressionKind::Unary(hir::Unary {
+                operator: self.operator,
+                operand: Box::new(operand),
+            }),
+        })
+    }
+
+    fn invalid_operand(&self, r#type: &Type) -> SemaError {
+        SemaError::InvalidOperand {
+            operator: self.operator.to_string(),
+            r#type: r#type.to_string(),
+            operand_span: self.operand.span(),
+            span: self.operator_span,
+        }
+    }
+}
+
+impl ConstantExpression {
+    /// Types the literal, negated if `negation` holds the span of a `-` applied to it. Without a suffix the
+    /// literal takes the `expected` type if it is of the same kind, otherwise integers default to `i64` and
+    /// floats to `f64`.
+    pub fn analyze(
+        &self,
+        expected: Option<&Type>,
+        negation: Option<Span>,
+    ) -> Result<hir::Expression, SemaError> {
+        let r#type = match (&self.suffix, &self.value, expected) {
+            (Some(suffix), _, _) => resolve_type(suffix)?,
+            (None, ConstantExpressionValue::UInt(_), Some(expected)) if expected.is_integer() => {
+                expected.clone()
+            }
+            (None, ConstantExpressionValue::Float(_), Some(expected)) if expected.is_float() => {
+                expected.clone()
+            }
+            (None, ConstantExpressionValue::UInt(_), _) => Type::Int {
+                signed: true,
+                bits: 64,
+            },
+            (None, ConstantExpressionValue::Float(_), _) => Type::Float { bits: 64 },
+        };
+
+        let span = negation.unwrap_or(self.span);
+        let negative = negation.is_some();
+
+        let kind = match self.value {
+            ConstantExpressionValue::UInt(val) => {
+                check_range(val, negative, &r#type, span)?;
+                ExpressionKind::Int(if negative { val.wrapping_neg() } else { val })
+            }
+            ConstantExpressionValue::Float(val) => {
+                ExpressionKind::Float(if negative { -val } else { val })
+            }
+        };
+
+        Ok(hir::Expression { kind, r#type })
+    }
+}
+
+/// Makes sure the integer literal `value`, negated if `negative` is set, fits into `r#type`
+fn check_range(value: u64, negative: bool, r#type: &Type, span: Span) -> Result<(), SemaError> {
+    let Some((min, max)) = r#type.int_range() else {
+        return Ok(());
+    };
+
+    let value = if negative {
+        -(value as i128)
+    } else {
+        value as i128
+    };
+
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(SemaError::LiteralOutOfRange {
+            literal: value.to_string(),
+            r#type: r#type.to_string(),
+            min,
+            max,
+            span,
+        })
+    }
+}
+
+impl VariableExpression {
+    /// The first use of an untyped local that `expected` a different type of the same kind decides its type,
+    /// which takes effect once the function is analyzed again
+    pub fn analyze(
+        &self,
+        scope: &FunctionScope,
+        expected: Option<&Type>,
+    ) -> Result<hir::Expression, SemaError> {
+        let local = scope.lookup(&self.name, self.span)?;
+        let r#type = scope.local(local).r#type.clone();
+
+        if let (Some(expected), Some(declaration)) = (expected, scope.untyped_declaration(local)) {
+            let same_kind = (expected.is_integer() && r#type.is_integer())
+                || (expected.is_float() && r#type.is_float());
+
+            if same_kind && *expected != r#type {
+                scope
+                    .inferred_types
+                    .borrow_mut()
+                    .entry(declaration)
+                    .or_insert_with(|| expected.clone());
+            }
+        }
+
+        Ok(hir::Expression {
+            kind: ExpressionKind::Local(local),
+            r#type,
+        })
+    }
+}
+
+impl CallExpression {
+    pub fn analyze(&self, scope: &mut FunctionScope) -> Result<hir::Expression, SemaError> {
+        let functions = scope.functions;
+        let Some(Some(signature)) = functions.get(&self.name) else {
+            return Err(SemaError::UnknownFunction(
+                self.name.clone(),
+                self.name_span,
+            ));
+        };
+
+        let count_matches = if signature.variadic {
+            self.args.len() >= signature.params.len()
+        } else {
+            self.args.len() == signature.params.len()
+        };
+        if !count_matches {
+            return Err(SemaError::ArgumentCount {
+                name: self.name.clone(),
+                expected: signature.params.len(),
+                found: self.args.len(),
+                variadic: signature.variadic,
+                span: self.span,
+                declared: signature.name_span,
+            });
+        }
+
+        let mut args = Vec::with_capacity(self.args.len());
+        for (arg, (r#type, span)) in self.args.iter().zip(&signature.params) {
+            let value = arg.analyze(scope, Some(r#type))?;
+
+            if !value.r#type.coerces_to(r#type) {
+                return Err(SemaError::TypesNotEqual {
+                    lhs: value.r#type.to_string(),
+                    lhs_span: arg.span(),
+                    rhs: r#type.to_string(),
+                    rhs_span: *span,
+                    span: arg.span(),
+                });
+            }
+
+            args.push(value);
+        }
+
+        //Like in C, literals passed as variadic arguments default to `i32` instead of `i64`
+        let int = Type::Int {
+            signed: true,
+            bits: 32,
+        };
+        for arg in &self.args[signature.params.len()..] {
+            let value = arg.analyze(scope, Some(&int))?;
+
+            if value.r#type.is_void() {
+                return Err(SemaError::VoidArgument(arg.span()));
+            }
+
+            args.push(value);
+        }
+
+        Ok(hir::Expression {
+            kind: ExpressionKind::Call(hir::Call {
+                function: signature.id,
+                args,
+            }),
+            r#type: signature.return_type.clone(),
+        })
+    }
+}
@@ -0,0 +1,121 @@
+use crate::parser::{Function, Scope};
+use crate::sema::hir::{self, FunctionId, Local};
+use crate::sema::statement::analyze_block;
+use crate::sema::{resolve_type, FunctionScope, SemaError, SemaWarning, Type};
+use crate::span::Span;
+use std::collections::HashMap;
+
+/// Resolved types of the parameters and the return value of a function, which is all a call needs
+#[derive(Debug)]
+pub struct Signature {
+    pub id: FunctionId,
+    /// Type of each parameter and where it is declared
+    pub params: Vec<(Type, Span)>,
+    pub return_type: Type,
+    /// Whether more arguments than parameters can be passed
+    pub variadic: bool,
+    pub name_span: Span,
+}
+
+impl Signature {
+    pub fn new(function: &Function, id: FunctionId) -> Result<Self, SemaError> {
+        let params = function
+            .params
+            .iter()
+            .map(|param| Ok((resolve_type(&param.r#type)?, param.span)))
+            .collect::<Result<_, SemaError>>()?;
+
+        Ok(Self {
+            id,
+            params,
+            return_type: resolve_type(&function.return_type)?,
+            variadic: function.variadic,
+            name_span: function.name_span,
+        })
+    }
+}
+
+/// Analyzes `function` and returns every error found in it, warnings are added to `warnings` even if the
+/// function has an error
+pub fn analyze_function(
+    function: &Function,
+    signatures: &HashMap<String, Option<Signature>>,
+    warnings: &mut Vec<SemaWarning>,
+) -> Result<hir::Function, Vec<SemaError>> {
+    let return_type = resolve_type(&function.return_type).map_err(|err| vec![err])?;
+
+    //Untyped locals learn their type from their first use, which is analyzed after their declaration. The
+    //function is analyzed again with the types learned so far until no new ones show up.
+    let mut inferred_types = HashMap::new();
+    loop {
+        let known = inferred_types.len();
+        let mut scope = FunctionScope::new(
+            signatures,
+            function.name.clone(),
+            return_type.clone(),
+            function.return_type.span,
+            inferred_types,
+        );
+
+        //Parameters are the first locals, in order
+        for param in &function.params {
+            match resolve_type(&param.r#type) {
+                Ok(r#type) => {
+                    scope.declare(Local {
+                        name: param.name.clone(),
+                        r#type,
+                        mutable: false,
+                        span: param.span,
+                    });
+                }
+                Err(err) => {
+                    scope.error(err);
+                    scope.declare_failed(param.name.clone());
+                }
+            }
+        }
+
+        scope.params = scope.locals.len();
+
+        let body = function
+            .body
+            .as_ref()
+            .map(|body| analyze_body(&mut scope, body));
+
+        inferred_types = scope.inferred_types.take();
+        if inferred_types.len() > known {
+            continue;
+        }
+
+        warnings.append(&mut scope.warnings);
+        if !scope.errors.is_empty() {
+            return Err(scope.errors);
+        }
+
+        return Ok(hir::Function {
+            name: function.name.clone(),
+            return_type,
+            params: scope.params,
+            locals: scope.locals,
+            variadic: function.variadic,
+            is_extern: function.is_extern,
+            body,
+        });
+    }
+}
+
+fn analyze_body(scope: &mut FunctionScope, body: &Scope) -> hir::Block {
+    let block = analyze_block(scope, body);
+
+    //Void functions may fall off the end of their body, everything else has to return explicitly. A statement
+    //with an error is left out of the block, so it may have been the one returning.
+    if !scope.return_type.is_void() && !block.diverges() && !scope.has_failed_statement {
+        //Point at the closing brace of the body
+        scope.error(SemaError::MissingReturn(
+            scope.name.clone(),
+            Span::new(body.span.end - 1, body.span.end),
+        ));
+    }
+
+    block
+}
@@ -0,0 +1,204 @@
+//! Typed representation of a program produced by semantic analysis. Every name is resolved and every
+//! expression has a type, so code generation can assume the program is well-formed.
+
+use crate::parser::Operator;
+use crate::sema::Type;
+use crate::span::Span;
+
+#[derive(Debug)]
+pub struct Program {
+    pub functions: Vec<Function>,
+}
+
+/// Index into `Function::locals`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LocalId(pub usize);
+
+/// Index into `Program::functions`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FunctionId(pub usize);
+
+/// A parameter or variable of a function
+#[derive(Debug)]
+pub struct Local {
+    pub name: String,
+    pub r#type: Type,
+    pub mutable: bool,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    pub return_type: Type,
+    /// The first `params` locals are the parameters, in order
+    pub params: usize,
+    pub locals: Vec<Local>,
+    pub variadic: bool,
+    /// Defined outside of the program and linked with the C calling convention, it has no body
+    pub is_extern: bool,
+    pub body: Option<Block>,
+}
+
+impl Function {
+    pub fn param_types(&self) -> impl Iterator<Item = &Type> {
+        self.locals[..self.params].iter().map(|local| &local.r#type)
+    }
+}
+
+#[derive(Debug)]
+pub struct Block {
+    pub statements: Vec<Statement>,
+}
+
+impl Block {
+    /// Whether control never reaches the end of the block, because every path returns, leaves an enclosing
+    /// loop or loops forever
+    pub fn diverges(&self) -> bool {
+        self.statements.iter().any(Statement::diverges)
+    }
+}
+
+#[derive(Debug)]
+pub enum Statement {
+    Declaration(Declaration),
+    Return(Return),
+    Block(Block),
+    Assign(Assign),
+    If(If),
+    While(While),
+    Loop(Loop),
+    Break(Break),
+    /// Jumps to the next iteration of the loop `target`, an index into the loops enclosing the statement with
+    /// the outermost one first
+    Continue(usize),
+    /// Expression evaluated for its side effects, its value is discarded
+    Expression(Expression),
+}
+
+impl Statement {
+    /// Whether control never continues with the statement after this one
+    pub fn diverges(&self) -> bool {
+        match self {
+            Statement::Return(_) | Statement::Break(_) | Statement::Continue(_) => true,
+            Statement::Block(block) => block.diverges(),
+            //Without an `else` the condition may be false and the `if` is skipped
+            Statement::If(stat) => {
+                stat.then_block.diverges()
+                    && stat
+                        .else_block
+                        .as_ref()
+                        .is_some_and(|block| block.diverges())
+            }
+            //A `while` ends once its condition is false, a `loop` only through `break`
+            Statement::Loop(stat) => !stat.has_break,
+            Statement::While(_)
+            | Statement::Declaration(_)
+            | Statement::Assign(_)
+            | Statement::Expression(_) => false,
+        }
+    }
+}
+
+/// Initializes a local, variables declared without an initializer start out zeroed
+#[derive(Debug)]
+pub struct Declaration {
+    pub local: LocalId,
+    pub value: Option<Expression>,
+}
+
+/// Stores `value` at `place`, a compound assignment first applies `operator` to the stored value and `value`
+#[derive(Debug)]
+pub struct Assign {
+    pub place: Place,
+    pub operator: Option<Operator>,
+    pub value: Expression,
+}
+
+/// `else if` is an `else` block holding another `If`
+#[derive(Debug)]
+pub struct If {
+    pub condition: Expression,
+    pub then_block: Block,
+    pub else_block: Option<Block>,
+}
+
+#[derive(Debug)]
+pub struct While {
+    pub condition: Expression,
+    pub body: Block,
+}
+
+#[derive(Debug)]
+pub struct Loop {
+    pub body: Block,
+    /// Whether a `break` leaves this loop, otherwise the code after it is unreachable
+    pub has_break: bool,
+    /// Type of the `break` values, `()` if the loop is left without a value
+    pub r#type: Type,
+}
+
+/// Leaves the loop `target`, an index into the loops enclosing the statement with the outermost one first
+#[derive(Debug)]
+pub struct Break {
+    pub target: usize,
+    pub value: Option<Expression>,
+}
+
+#[derive(Debug)]
+pub struct Return {
+    pub value: Option<Expression>,
+}
+
+#[derive(Debug)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub r#type: Type,
+}
+
+#[derive(Debug)]
+pub enum ExpressionKind {
+    /// Bit pattern of an integer constant, negative values are stored in two's complement
+    Int(u64),
+    Float(f64),
+    /// Value of a local
+    Local(LocalId),
+    Binary(Binary),
+    /// `-`, `!` or `~` applied to an operand
+    Unary(Unary),
+    AddressOf(Place),
+    /// Value behind a pointer
+    Deref(Box<Expression>),
+    /// `loop` evaluating to the value of its `break`
+    Loop(Loop),
+    Call(Call),
+    /// Pointer to a null-terminated copy of the string in global memory
+    String(String),
+}
+
+#[derive(Debug)]
+pub struct Call {
+    pub function: FunctionId,
+    pub args: Vec<Expression>,
+}
+
+#[derive(Debug)]
+pub struct Binary {
+    pub operator: Operator,
+    pub lhs: Box<Expression>,
+    pub rhs: Box<Expression>,
+}
+
+#[derive(Debug)]
+pub struct Unary {
+    pub operator: Operator,
+    pub operand: Box<Expression>,
+}
+
+/// Expression denoting a memory location
+#[derive(Debug)]
+pub enum Place {
+    Local(LocalId),
+    /// Location a pointer points to
+    Deref(Box<Expression>),
+}
@@ -0,0 +1,526 @@
+mod expression;
+mod function;
+pub mod hir;
+mod statement;
+mod types;
+
+pub use types::Type;
+
+use crate::diagnostic::Diagnostic;
+use crate::parser::{Element, Label, SourceFile};
+use crate::sema::function::{analyze_function, Signature};
+use crate::sema::hir::{FunctionId, Local, LocalId, Place, Program};
+use crate::span::Span;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SemaError {
+    #[error("Unknown type: {0}")]
+    UnknownType(String, Span),
+
+    #[error("Unknown variable: {0}")]
+    UnknownVariable(String, Span),
+
+    #[error("Variable {name} is not in scope")]
+    VariableOutOfScope {
+        name: String,
+        span: Span,
+        declared: Span,
+    },
+
+    #[error("Function `{name}` is defined multiple times")]
+    DuplicateFunction {
+        name: String,
+        span: Span,
+        previous: Span,
+    },
+
+    #[error("Types are not equal: `{lhs}` and `{rhs}`")]
+    TypesNotEqual {
+        lhs: String,
+        lhs_span: Span,
+        rhs: String,
+        rhs_span: Span,
+        span: Span,
+    },
+
+    #[error("Operator `{operator}` cannot be applied to type `{r#type}`")]
+    InvalidOperand {
+        operator: String,
+        r#type: String,
+        operand_span: Span,
+        span: Span,
+    },
+
+    #[error("Literal `{literal}` does not fit into `{r#type}`")]
+    LiteralOutOfRange {
+        literal: String,
+        r#type: String,
+        min: i128,
+        max: i128,
+        span: Span,
+    },
+
+    #[error("Cannot assign to immutable variable {name}")]
+    ImmutableAssignment {
+        name: String,
+        span: Span,
+        declared: Span,
+        parameter: bool,
+    },
+
+    #[error("Cannot assign through a pointer of type `{r#type}`")]
+    ImmutablePointee {
+        r#type: String,
+        span: Span,
+        pointer_span: Span,
+    },
+
+    #[error("Cannot assign to this expression")]
+    InvalidAssignmentTarget(Span),
+
+    #[error("Expected a condition of type `bool`, found `{r#type}`")]
+    NonBoolCondition { r#type: String, span: Span },
+
+    #[error("`{keyword}` outside of a loop")]
+    JumpOutsideLoop { keyword: &'static str, span: Span },
+
+    #[error("Use of undeclared label `'{0}`")]
+    UnknownLabel(String, Span),
+
+    #[error("`break` with a value is only allowed inside `loop`")]
+    BreakValueInWhile(Span),
+
+    #[error("Variable {0} has type `()`")]
+    VoidVariable(String, Span),
+
+    #[error("Cannot take the address of a temporary value")]
+    AddressOfTemporary(Span),
+
+    #[error("Function {0} does not return a value on every path")]
+    MissingReturn(String, Span),
+
+    #[error("Function {0} does not return a value")]
+    UnexpectedReturnValue(String, Span),
+
+    #[error("Unknown function: {0}")]
+    UnknownFunction(String, Span),
+
+    #[error(
+        "Function `{name}` takes {}{} but {found} {} supplied",
+        if *.variadic { "at least " } else { "" },
+        arguments(*.expected),
+        if *.found == 1 { "was" } else { "were" }
+    )]
+    ArgumentCount {
+        name: String,
+        expected: usize,
+        found: usize,
+        variadic: bool,
+        span: Span,
+        declared: Span,
+    },
+
+    #[error("Argument has type `()`")]
+    VoidArgument(Span),
+}
+
+impl From<&SemaError> for Diagnostic {
+    fn from(err: &SemaError) -> Self {
+        let diagnostic = Diagnostic::error(err.to_string());
+
+        match err {
+            SemaError::UnknownType(_, span) => diagnostic.with_label(*span, "unknown type"),
+            SemaError::UnknownVariable(_, span) => {
+                diagnostic.with_label(*span, "not found in this scope")
+            }
+            SemaError::VariableOutOfScope { span, declared, .. } => diagnostic
+                .with_label(*span, "not found in this scope")
+                .with_secondary_label(*declared, "declared in a block that has already ended"),
+            SemaError::DuplicateFunction { span, previous, .. } => diagnostic
+                .with_label(*span, "redefined here")
+                .with_secondary_label(*previous, "first defined here"),
+            SemaError::TypesNotEqual {
+                lhs,
+                lhs_span,
+                rhs,
+                rhs_span,
+                span,
+            } => diagnostic
+                .with_label(*span, "")
+                .with_secondary_label(*lhs_span, format!("this is of type `{}`", lhs))
+                .with_secondary_label(*rhs_span, format!("this is of type `{}`", rhs)),
+            SemaError::InvalidOperand {
+                r#type,
+                operand_span,
+                span,
+                ..
+            } => diagnostic
+                .with_label(*span, "")
+                .with_secondary_label(*operand_span, format!("this is of type `{}`", r#type)),
+            SemaError::LiteralOutOfRange {
+                r#type,
+                min,
+                max,
+                span,
+                ..
+            } => diagnostic.with_label(
+                *span,
+                format!("`{}` ranges from {} to {}", r#type, min, max),
+            ),
+            SemaError::ImmutableAssignment {
+                span,
+                declared,
+                parameter: true,
+                ..
+            } => diagnostic
+                .with_label(*span, "cannot assign to a parameter")
+                .with_secondary_label(*declared, "declared here")
+                .with_help("copy the parameter into a `var` binding to modify it"),
+            SemaError::ImmutableAssignment { span, declared, .. } => diagnostic
+                .with_label(*span, "cannot assign to a `let` binding")
+                .with_secondary_label(*declared, "declared here")
+                .with_help("declare the variable with `var` to make it mutable"),
+            SemaError::ImmutablePointee {
+                r#type,
+                span,
+                pointer_span,
+            } => diagnostic
+                .with_label(
+                    *span,
+                    "cannot assign to the value behind an immutable pointer",
+                )
+                .with_secondary_label(*pointer_span, format!("this is of type `{}`", r#type))
+                .with_help(
+                    "only a `*var` pointer, taken from a `var` binding, can be assigned through",
+                ),
+            SemaError::InvalidAssignmentTarget(span) => diagnostic.with_label(
+                *span,
+                "this is neither a variable nor a dereferenced pointer",
+            ),
+            SemaError::NonBoolCondition { r#type, span } => diagnostic
+                .with_label(*span, format!("this is of type `{}`", r#type))
+                .with_help("compare the value, e.g. `value != 0`"),
+            SemaError::JumpOutsideLoop { keyword, span } => {
+                diagnostic.with_label(*span, format!("cannot `{}` outside of a loop", keyword))
+            }
+            SemaError::UnknownLabel(_, span) => {
+                diagnostic.with_label(*span, "no enclosing loop has this label")
+            }
+            SemaError::BreakValueInWhile(span) => diagnostic
+                .with_label(*span, "a `while` loop cannot produce a value")
+                .with_help("use `loop` and check the condition inside of it"),
+            SemaError::VoidVariable(_, span) => {
+                diagnostic.with_label(*span, "the initializer does not produce a value")
+            }
+            SemaError::AddressOfTemporary(span) => diagnostic
+                .with_label(*span, "this value is not stored anywhere")
+                .with_help("store the value in a variable and take the address of the variable"),
+            SemaError::MissingReturn(_, span) => diagnostic
+                .with_label(*span, "expected a return value")
+                .with_help("add a `return` statement with a value"),
+            SemaError::UnexpectedReturnValue(_, span) => diagnostic
+                .with_label(*span, "the function has no return type")
+                .with_help("declare a return type after the parameter list"),
+            SemaError::UnknownFunction(_, span) => {
+                diagnostic.with_label(*span, "no function with this name in the file")
+            }
+            SemaError::ArgumentCount {
+                expected,
+                span,
+                declared,
+                ..
+            } => diagnostic
+                .with_label(*span, format!("expected {}", arguments(*expected)))
+                .with_secondary_label(*declared, "function defined here"),
+            SemaError::VoidArgument(span) => {
+                diagnostic.with_label(*span, "this expression does not produce a value")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SemaWarning {
+    #[error("Unreachable statement")]
+    UnreachableCode { span: Span, cause: Span },
+
+    #[error("Unused value of type `{r#type}`")]
+    UnusedValue { r#type: String, span: Span },
+}
+
+impl From<&SemaWarning> for Diagnostic {
+    fn from(warning: &SemaWarning) -> Self {
+        let diagnostic = Diagnostic::warning(warning.to_string());
+
+        match warning {
+            SemaWarning::UnreachableCode { span, cause } => diagnostic
+                .with_label(*span, "")
+                .with_secondary_label(*cause, "any code following this is never executed"),
+            SemaWarning::UnusedValue { span, .. } => diagnostic
+                .with_label(*span, "the value of this expression is discarded")
+                .with_help("assign it to a variable if it is needed"),
+        }
+    }
+}
+
+/// Resolves names and checks types of `source_file`, errors are collected per statement so that all of them
+/// are reported at once. On success the warnings are returned along with the program, otherwise they are part of
+/// the error.
+pub fn analyze(source_file: &SourceFile) -> Result<(Program, Vec<Diagnostic>), Vec<Diagnostic>> {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let mut defined = HashMap::new();
+    let mut signatures = HashMap::new();
+    //Functions that are analyzed, indexed by `FunctionId`
+    let mut analyzed = Vec::new();
+
+    //Signatures are collected first, so a function can call functions defined after it
+    for element in source_file.iter() {
+        let Element::Function(function) = element;
+
+        if let Some(previous) = defined.insert(function.name.as_str(), function.name_span) {
+            errors.push(SemaError::DuplicateFunction {
+                name: function.name.clone(),
+                span: function.name_span,
+                previous,
+            });
+            continue;
+        }
+
+        //An error in the signature is reported when the function is analyzed
+        let signature = Signature::new(function, FunctionId(analyzed.len())).ok();
+        signatures.insert(function.name.clone(), signature);
+        analyzed.push(function);
+    }
+
+    let mut functions = Vec::new();
+    for function in analyzed {
+        match analyze_function(function, &signatures, &mut warnings) {
+            Ok(function) => functions.push(function),
+            Err(mut function_errors) => errors.append(&mut function_errors),
+        }
+    }
+
+    let warnings = warnings.iter().map(Diagnostic::from);
+    if errors.is_empty() {
+        Ok((Program { functions }, warnings.collect()))
+    } else {
+        Err(errors
+            .iter()
+            .map(Diagnostic::from)
+            .chain(warnings)
+            .collect())
+    }
+}
+
+/// State of the function being analyzed
+pub struct FunctionScope<'a> {
+    /// Signatures of all functions of the file by name, `None` for a function whose signature has an error
+    pub functions: &'a HashMap<String, Option<Signature>>,
+    pub name: String,
+    pub return_type: Type,
+    pub return_type_span: Span,
+    /// The first `params` locals are the parameters
+    pub params: usize,
+    pub locals: Vec<Local>,
+    /// Loops enclosing the current statement, the innermost loop is last
+    pub loops: Vec<LoopTarget>,
+    pub warnings: Vec<SemaWarning>,
+    /// Errors of the statements analyzed so far, each of them left out its statement
+    pub errors: Vec<SemaError>,
+    /// Whether a statement was left out, which is also the case for errors that are not reported
+    pub has_failed_statement: bool,
+    /// Type the first use of an untyped local expects, keyed by the span of its name. Filled in while analyzing
+    /// and passed to the next attempt, see `analyze_function`.
+    pub inferred_types: RefCell<HashMap<Span, Type>>,
+    //Names visible in each open block, the innermost block is last. `None` marks a declaration that had an error.
+    scopes: Vec<HashMap<String, Option<LocalId>>>,
+    //Immutable locals initialized with an untyped expression and the span of their name, their type is decided
+    //by their first use
+    untyped_locals: HashMap<LocalId, Span>,
+}
+
+impl<'a> FunctionScope<'a> {
+    pub fn new(
+        functions: &'a HashMap<String, Option<Signature>>,
+        name: String,
+        return_type: Type,
+        return_type_span: Span,
+        inferred_types: HashMap<Span, Type>,
+    ) -> Self {
+        Self {
+            functions,
+            name,
+            return_type,
+            return_type_span,
+            params: 0,
+            locals: Vec::new(),
+            loops: Vec::new(),
+            warnings: Vec::new(),
+            errors: Vec::new(),
+            has_failed_statement: false,
+            inferred_types: RefCell::new(inferred_types),
+            //The outermost scope holds the parameters
+            scopes: vec![HashMap::new()],
+            untyped_locals: HashMap::new(),
+        }
+    }
+
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn exit_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Adds a local to the innermost scope, it shadows locals with the same name declared earlier
+    pub fn declare(&mut self, local: Local) -> LocalId {
+        let id = LocalId(self.locals.len());
+        self.bind(local.name.clone(), Some(id));
+        self.locals.push(local);
+        id
+    }
+
+    /// Adds a local whose type is decided by its first use, `span` is where its name is declared
+    pub fn declare_untyped(&mut self, local: Local, span: Span) -> LocalId {
+        let id = self.declare(local);
+        self.untyped_locals.insert(id, span);
+        id
+    }
+
+    /// Shadows `name` in the innermost scope after its declaration had an error, uses of it are not reported
+    /// again until the scope ends
+    pub fn declare_failed(&mut self, name: String) {
+        self.bind(name, None);
+    }
+
+    fn bind(&mut self, name: String, id: Option<LocalId>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name, id);
+        }
+    }
+
+    /// The innermost binding of `name`, `Some(None)` if its declaration had an error
+    fn binding(&self, name: &str) -> Option<Option<LocalId>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    /// Finds the innermost visible local named `name`
+    pub fn lookup(&self, name: &str, span: Span) -> Result<LocalId, SemaError> {
+        match self.binding(name) {
+            Some(Some(id)) => return Ok(id),
+            Some(None) => return Err(SemaError::UnknownVariable(name.to_owned(), span)),
+            None => {}
+        }
+
+        //Locals are only added once they are declared, so a match that isn't visible was declared in a
+        //block that has already ended
+        match self.locals.iter().rev().find(|local| local.name == name) {
+            Some(local) => Err(SemaError::VariableOutOfScope {
+                name: name.to_owned(),
+                span,
+                declared: local.span,
+            }),
+            None => Err(SemaError::UnknownVariable(name.to_owned(), span)),
+        }
+    }
+
+    /// Where an untyped local is declared, `None` for every other local
+    pub fn untyped_declaration(&self, id: LocalId) -> Option<Span> {
+        self.untyped_locals.get(&id).copied()
+    }
+
+    /// Records `err`, unless it is caused by a variable whose declaration or a function whose signature already
+    /// had an error
+    pub fn error(&mut self, err: SemaError) {
+        self.has_failed_statement = true;
+
+        match &err {
+            SemaError::UnknownVariable(name, _) if matches!(self.binding(name), Some(None)) => {
+                return
+            }
+            SemaError::UnknownFunction(name, _)
+                if matches!(self.functions.get(name), Some(None)) =>
+            {
+                return;
+            }
+            _ => {}
+        }
+
+        self.errors.push(err);
+    }
+
+    pub fn local(&self, id: LocalId) -> &Local {
+        &self.locals[id.0]
+    }
+
+    /// Whether `place` can be assigned to, which a pointer to it passes on
+    pub fn is_mutable(&self, place: &Place) -> bool {
+        match place {
+            Place::Local(id) => self.local(*id).mutable,
+            Place::Deref(pointer) => pointer.r#type.is_mutable_pointer(),
+        }
+    }
+
+    /// Finds the loop a `break` or `continue` refers to, the innermost loop if there is no label. Returns its
+    /// index in `loops`.
+    pub fn loop_target(
+        &self,
+        label: Option<&Label>,
+        keyword: &'static str,
+        span: Span,
+    ) -> Result<usize, SemaError> {
+        match label {
+            Some(label) => self
+                .loops
+                .iter()
+                .rposition(|target| target.label.as_deref() == Some(label.name.as_str()))
+                .ok_or_else(|| SemaError::UnknownLabel(label.name.clone(), label.span)),
+            None => self
+                .loops
+                .len()
+                .checked_sub(1)
+                .ok_or(SemaError::JumpOutsideLoop { keyword, span }),
+        }
+    }
+}
+
+/// Loop that `break` and `continue` can refer to
+pub struct LoopTarget {
+    pub label: Option<String>,
+    /// Only `loop` can be left with a value, `while` may end without running a `break`
+    pub accepts_value: bool,
+    pub has_break: bool,
+    /// Type the context of a `loop` expression expects, untyped `break` values take it
+    pub expected: Option<Type>,
+    /// Type of the value of the first `break` and where it is
+    pub value: Option<(Type, Span)>,
+}
+
+/// `count` followed by "argument" or "arguments"
+fn arguments(count: usize) -> String {
+    if count == 1 {
+        "1 argument".to_owned()
+    } else {
+        format!("{} arguments", count)
+    }
+}
+
+/// Resolves a type written in the source
+pub fn resolve_type(r#type: &crate::parser::Type) -> Result<Type, SemaError> {
+    let resolved = Type::from_name(&r#type.name)
+        .ok_or_else(|| SemaError::UnknownType(r#type.name.clone(), r#type.span))?;
+
+    if r#type.is_pointer {
+        Ok(resolved.pointer_to(r#type.is_mutable))
+    } else {
+        Ok(resolved)
+    }
+}
@@ -0,0 +1,390 @@
+use crate::parser::{
+    AssignmentStatement, BreakStatement, ElseBranch, Expression, IfStatement, LoopStatement,
+    ReassignmentStatement, ReturnStatement, Scope, Statement, WhileStatement,
+};
+use crate::sema::expression::supports;
+use crate::sema::hir::{self, Local, Place};
+use crate::sema::{resolve_type, FunctionScope, LoopTarget, SemaError, SemaWarning, Type};
+
+/// Analyzes the statements of `block` in a new scope, the variables declared inside are dropped at its end
+pub fn analyze_block(scope: &mut FunctionScope, block: &Scope) -> hir::Block {
+    scope.enter_scope();
+    let statements = analyze_statements(scope, &block.elements);
+    scope.exit_scope();
+
+    hir::Block { statements }
+}
+
+/// Analyzes a sequence of statements and warns about the ones that can never be reached. A statement with an
+/// error is added to `scope.errors` and left out, analysis continues with the next one.
+fn analyze_statements(scope: &mut FunctionScope, elements: &[Statement]) -> Vec<hir::Statement> {
+    let mut statements = Vec::with_capacity(elements.len());
+    let mut warned = false;
+
+    for (position, statement) in elements.iter().enumerate() {
+        if !warned && statements.last().is_some_and(hir::Statement::diverges) {
+            let last = elements.last().unwrap_or(statement);
+            scope.warnings.push(SemaWarning::UnreachableCode {
+                span: statement.span().to(last.span()),
+                cause: elements[position - 1].span(),
+            });
+            warned = true;
+        }
+
+        match statement.analyze(scope) {
+            Ok(analyzed) => statements.push(analyzed),
+            Err(err) => {
+                scope.error(err);
+                //Uses of a variable whose declaration had an error would only repeat that error
+                if let Statement::Declaration(declaration) = statement {
+                    scope.declare_failed(declaration.ident.clone());
+                }
+            }
+        }
+    }
+
+    statements
+}
+
+impl Statement {
+    pub fn analyze(&self, scope: &mut FunctionScope) -> Result<hir::Statement, SemaError> {
+        match self {
+            Statement::Return(stat) => stat.analyze(scope),
+            Statement::Declaration(stat) => stat.analyze(scope),
+            Statement::Block(block) => Ok(hir::Statement::Block(analyze_block(scope, block))),
+            Statement::Reassignment(stat) => stat.analyze(scope),
+            Statement::If(stat) => Ok(hir::Statement::If(stat.analyze(scope)?)),
+            Statement::While(stat) => stat.analyze(scope),
+            Statement::Loop(stat) => Ok(hir::Statement::Loop(stat.analyze(scope, None)?)),
+            Statement::Break(stat) => Ok(hir::Statement::Break(stat.analyze(scope)?)),
+            Statement::Expression(stat) => {
+                let value = stat.expression.analyze(scope, None)?;
+
+                if !value.r#type.is_void() {
+                    scope.warnings.push(SemaWarning::UnusedValue {
+                        r#type: value.r#type.to_string(),
+                        span: stat.expression.span(),
+                    });
+                }
+
+                Ok(hir::Statement::Expression(value))
+            }
+            Statement::Continue(stat) => {
+                let target = scope.loop_target(stat.label.as_ref(), "continue", stat.span)?;
+
+                Ok(hir::Statement::Continue(target))
+            }
+        }
+    }
+}
+
+impl BreakStatement {
+    pub fn analyze(&self, scope: &mut FunctionScope) -> Result<hir::Break, SemaError> {
+        let target = scope.loop_target(self.label.as_ref(), "break", self.span)?;
+
+        let value = match &self.value {
+            Some(value) if !scope.loops[target].accepts_value => {
+                return Err(SemaError::BreakValueInWhile(value.span()));
+            }
+            Some(value) => {
+                //Every `break` has to agree with the first one, which in turn takes the type of the context
+                let loop_target = &scope.loops[target];
+                let expected = match &loop_target.value {
+                    Some((r#type, _)) => Some(r#type.clone()),
+                    None => loop_target.expected.clone(),
+                };
+
+                Some(value.analyze(scope, expected.as_ref())?)
+            }
+            None => None,
+        };
+
+        let r#type = value
+            .as_ref()
+            .map_or(Type::Void, |value| value.r#type.clone());
+        let span = self.value.as_ref().map_or(self.span, Expression::span);
+
+        let loop_target = &mut scope.loops[target];
+        match &loop_target.value {
+            Some((previous, previous_span)) if *previous != r#type => {
+                return Err(SemaError::TypesNotEqual {
+                    lhs: previous.to_string(),
+                    lhs_span: *previous_span,
+                    rhs: r#type.to_string(),
+                    rhs_span: span,
+                    span,
+                });
+            }
+            Some(_) => {}
+            None => loop_target.value = Some((r#type, span)),
+        }
+        loop_target.has_break = true;
+
+        Ok(hir::Break { target, value })
+    }
+}
+
+impl AssignmentStatement {
+    pub fn analyze(&self, scope: &mut FunctionScope) -> Result<hir::Statement, SemaError> {
+        let declared_type = self.r#type.as_ref().map(resolve_type).transpose()?;
+
+        //An immutable local without a type takes the type its first use expects if its initializer has none
+        let untyped = !self.mutable
+            && declared_type.is_none()
+            && self.rhs.as_ref().is_some_and(|rhs| rhs.is_untyped(scope));
+        let inferred = untyped
+            .then(|| scope.inferred_types.borrow().get(&self.ident_span).cloned())
+            .flatten();
+        let expected = declared_type.clone().or(inferred);
+
+        let value = match &self.rhs {
+            Some(rhs) => {
+                let value = rhs.analyze(scope, expected.as_ref())?;
+
+                if value.r#type.is_void() {
+                    return Err(SemaError::VoidVariable(self.ident.clone(), rhs.span()));
+                }
+
+                if let (Some(declared_type), Some(r#type)) = (&declared_type, &self.r#type) {
+                    if !value.r#type.coerces_to(declared_type) {
+                        return Err(SemaError::TypesNotEqual {
+                            lhs: value.r#type.to_string(),
+                            lhs_span: rhs.span(),
+                            rhs: declared_type.to_string(),
+                            rhs_span: r#type.span,
+                            span: rhs.span(),
+                        });
+                    }
+                }
+
+                Some(value)
+            }
+            None => None,
+        };
+
+        //The parser only accepts a missing initializer together with a type
+        let r#type = match (declared_type, &value) {
+            (Some(r#type), _) => r#type,
+            (None, Some(value)) => value.r#type.clone(),
+            (None, None) => unreachable!("declaration without type and initializer"),
+        };
+
+        //Declared after the initializer, so `let x = x + 1;` refers to an earlier `x`
+        let local = Local {
+            name: self.ident.clone(),
+            r#type,
+            mutable: self.mutable,
+            span: self.ident_span,
+        };
+        let local = if untyped {
+            scope.declare_untyped(local, self.ident_span)
+        } else {
+            scope.declare(local)
+        };
+
+        Ok(hir::Statement::Declaration(hir::Declaration {
+            local,
+            value,
+        }))
+    }
+}
+
+impl ReturnStatement {
+    pub fn analyze(&self, scope: &mut FunctionScope) -> Result<hir::Statement, SemaError> {
+        let value = match &self.expression {
+            None if !scope.return_type.is_void() => {
+                return Err(SemaError::MissingReturn(scope.name.clone(), self.span));
+            }
+            None => None,
+            Some(expression) if scope.return_type.is_void() => {
+                return Err(SemaError::UnexpectedReturnValue(
+                    scope.name.clone(),
+                    expression.span(),
+                ));
+            }
+            Some(expression) => {
+                let return_type = scope.return_type.clone();
+                let value = expression.analyze(scope, Some(&return_type))?;
+
+                if !value.r#type.coerces_to(&scope.return_type) {
+                    return Err(SemaError::TypesNotEqual {
+                        lhs: value.r#type.to_string(),
+                        lhs_span: expression.span(),
+                        rhs: scope.return_type.to_string(),
+                        rhs_span: scope.return_type_span,
+                        span: expression.span(),
+                    });
+                }
+
+                Some(value)
+            }
+        };
+
+        Ok(hir::Statement::Return(hir::Return { value }))
+    }
+}
+
+impl ReassignmentStatement {
+    pub fn analyze(&self, scope: &mut FunctionScope) -> Result<hir::Statement, SemaError> {
+        if !self.target.is_place() {
+            return Err(SemaError::InvalidAssignmentTarget(self.target.span()));
+        }
+
+        let (place, r#type) = self.target.analyze_place(scope)?;
+
+        //Storing through a pointer needs a `*var` pointer, the variable holding it may be immutable
+        match &place {
+            Place::Local(id) if !scope.local(*id).mutable => {
+                let local = scope.local(*id);
+                return Err(SemaError::ImmutableAssignment {
+                    name: local.name.clone(),
+                    span: self.span,
+                    declared: local.span,
+                    parameter: id.0 < scope.params,
+                });
+            }
+            Place::Deref(pointer) if !pointer.r#type.is_mutable_pointer() => {
+                //A dereferenced place is always written as `*pointer`
+                let pointer_span = match &self.target {
+                    Expression::Unary(unary) => unary.operand.span(),
+                    target => target.span(),
+                };
+
+                return Err(SemaError::ImmutablePointee {
+                    r#type: pointer.r#type.to_string(),
+                    span: self.span,
+                    pointer_span,
+                });
+            }
+            _ => {}
+        }
+
+        let value = self.value.analyze(scope, Some(&r#type))?;
+        if !value.r#type.coerces_to(&r#type) {
+            return Err(SemaError::TypesNotEqual {
+                lhs: r#type.to_string(),
+                lhs_span: self.target.span(),
+                rhs: value.r#type.to_string(),
+                rhs_span: self.value.span(),
+                span: self.operator_span,
+            });
+        }
+
+        let operator = self
+            .operator
+            .and_then(|operator| operator.compound_operator());
+        if let Some(compound) = operator {
+            if !supports(compound, &r#type) {
+                return Err(SemaError::InvalidOperand {
+                    operator: self.operator.map(|op| op.to_string()).unwrap_or_default(),
+                    r#type: r#type.to_string(),
+                    operand_span: self.target.span(),
+                    span: self.operator_span,
+                });
+            }
+        }
+
+        Ok(hir::Statement::Assign(hir::Assign {
+            place,
+            operator,
+            value,
+        }))
+    }
+}
+
+impl IfStatement {
+    pub fn analyze(&self, scope: &mut FunctionScope) -> Result<hir::If, SemaError> {
+        let condition = analyze_condition(scope, &self.condition)?;
+        let then_block = analyze_block(scope, &self.then_block);
+
+        let else_block = match &self.else_branch {
+            Some(ElseBranch::Block(block)) => Some(analyze_block(scope, block)),
+            Some(ElseBranch::If(stat)) => Some(hir::Block {
+                statements: vec![hir::Statement::If(stat.analyze(scope)?)],
+            }),
+            None => None,
+        };
+
+        Ok(hir::If {
+            condition,
+            then_block,
+            else_block,
+        })
+    }
+}
+
+impl WhileStatement {
+    pub fn analyze(&self, scope: &mut FunctionScope) -> Result<hir::Statement, SemaError> {
+        let condition = analyze_condition(scope, &self.condition)?;
+        let target = LoopTarget {
+            label: self.label.as_ref().map(|label| label.name.clone()),
+            accepts_value: false,
+            has_break: false,
+            expected: None,
+            value: None,
+        };
+        let (body, _) = analyze_loop_body(scope, &self.body, target);
+
+        Ok(hir::Statement::While(hir::While { condition, body }))
+    }
+}
+
+impl LoopStatement {
+    /// Analyzes the loop, its type is the type of its `break` values. A loop without `break` never produces a
+    /// value, so it takes the `expected` type.
+    pub fn analyze(
+        &self,
+        scope: &mut FunctionScope,
+        expected: Option<&Type>,
+    ) -> Result<hir::Loop, SemaError> {
+        let target = LoopTarget {
+            label: self.label.as_ref().map(|label| label.name.clone()),
+            accepts_value: true,
+            has_break: false,
+            expected: expected.cloned(),
+            value: None,
+        };
+        let (body, target) = analyze_loop_body(scope, &self.body, target);
+
+        let r#type = match target.value {
+            Some((r#type, _)) => r#type,
+            None => expected.cloned().unwrap_or(Type::Void),
+        };
+
+        Ok(hir::Loop {
+            body,
+            has_break: target.has_break,
+            r#type,
+        })
+    }
+}
+
+/// Analyzes the body of a loop with the loop as the target of `break` and `continue`
+fn analyze_loop_body(
+    scope: &mut FunctionScope,
+    body: &Scope,
+    target: LoopTarget,
+) -> (hir::Block, LoopTarget) {
+    scope.loops.push(target);
+    let body = analyze_block(scope, body);
+    let target = scope.loops.pop().expect("loop target was pushed above");
+
+    (body, target)
+}
+
+/// Analyzes the condition of a branch or loop, which has to be a `bool`
+fn analyze_condition(
+    scope: &mut FunctionScope,
+    condition: &Expression,
+) -> Result<hir::Expression, SemaError> {
+    let value = condition.analyze(scope, Some(&Type::Bool))?;
+
+    if value.r#type.is_bool() {
+        Ok(value)
+    } else {
+        Err(SemaError::NonBoolCondition {
+            r#type: value.r#type.to_string(),
+            span: condition.span(),
+        })
+    }
+}
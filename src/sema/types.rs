@@ -0,0 +1,160 @@
+use std::fmt::{Display, Formatter};
+
+/// Type of a value after semantic analysis
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Type {
+    Int {
+        signed: bool,
+        bits: u32,
+    },
+    Float {
+        bits: u32,
+    },
+    Bool,
+    Void,
+    /// Only a `mutable` pointer can be used to assign to the value it points to
+    Pointer {
+        pointee: Box<Type>,
+        mutable: bool,
+    },
+}
+
+impl Type {
+    /// Looks up a builtin type by its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        let r#type = match name {
+            "i8" => Type::Int {
+                signed: true,
+                bits: 8,
+            },
+            "i16" => Type::Int {
+                signed: true,
+                bits: 16,
+            },
+            "i32" => Type::Int {
+                signed: true,
+                bits: 32,
+            },
+            "i64" => Type::Int {
+                signed: true,
+                bits: 64,
+            },
+            "u8" => Type::Int {
+                signed: false,
+                bits: 8,
+            },
+            "u16" => Type::Int {
+                signed: false,
+                bits: 16,
+            },
+            "u32" => Type::Int {
+                signed: false,
+                bits: 32,
+            },
+            "u64" => Type::Int {
+                signed: false,
+                bits: 64,
+            },
+            "f32" => Type::Float { bits: 32 },
+            "f64" => Type::Float { bits: 64 },
+            "bool" => Type::Bool,
+            "()" => Type::Void,
+            _ => return None,
+        };
+
+        Some(r#type)
+    }
+
+    pub fn pointer_to(self, mutable: bool) -> Self {
+        Type::Pointer {
+            pointee: Box::new(self),
+            mutable,
+        }
+    }
+
+    /// Type a pointer points to, `None` if `self` is no pointer
+    pub fn pointee(&self) -> Option<&Type> {
+        match self {
+            Type::Pointer { pointee, .. } => Some(pointee),
+            _ => None,
+        }
+    }
+
+    /// `bool` is an integer in LLVM but not in the language
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Type::Int { .. })
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self, Type::Int { signed: true, .. })
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Type::Float { .. })
+    }
+
+    pub fn is_bool(&self) -> bool {
+        *self == Type::Bool
+    }
+
+    pub fn is_pointer(&self) -> bool {
+        matches!(self, Type::Pointer { .. })
+    }
+
+    pub fn is_mutable_pointer(&self) -> bool {
+        matches!(self, Type::Pointer { mutable: true, .. })
+    }
+
+    /// Whether a value of this type can be used where a `target` is expected, which also allows passing a
+    /// `*var T` as a `*T`
+    pub fn coerces_to(&self, target: &Type) -> bool {
+        match (self, target) {
+            (
+                Type::Pointer { pointee, mutable },
+                Type::Pointer {
+                    pointee: target_pointee,
+                    mutable: target_mutable,
+                },
+            ) => pointee == target_pointee && (*mutable || !*target_mutable),
+            _ => self == target,
+        }
+    }
+
+    pub fn is_void(&self) -> bool {
+        *self == Type::Void
+    }
+
+    /// Smallest and largest value of an integer type
+    pub fn int_range(&self) -> Option<(i128, i128)> {
+        match *self {
+            Type::Int { signed: true, bits } => {
+                Some((-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1))
+            }
+            Type::Int {
+                signed: false,
+                bits,
+            } => Some((0, (1i128 << bits) - 1)),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Int { signed: true, bits } => write!(f, "i{}", bits),
+            Type::Int {
+                signed: false,
+                bits,
+            } => write!(f, "u{}", bits),
+            Type::Float { bits } => write!(f, "f{}", bits),
+            Type::Bool => f.write_str("bool"),
+            Type::Void => f.write_str("()"),
+            Type::Pointer {
+                pointee,
+                mutable: true,
+            } => write!(f, "*var {}", pointee),
+            Type::Pointer { pointee, .. } => write!(f, "*{}", pointee),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Helpers shared by the integration tests, the crate has no library target so the tests drive the `maga` binary.
+
+use std::fs;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Runs maga with `args` followed by the path of a temporary file holding `source`
+pub fn maga(args: &[&str], source: &str) -> Output {
+    //Every test binary runs in its own process, the counter keeps the files of parallel tests apart
+    let path = std::env::temp_dir().join(format!(
+        "maga-test-{}-{}.maga",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&path, source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_maga"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .expect("Failed to run maga");
+    let _ = fs::remove_file(&path);
+
+    output
+}
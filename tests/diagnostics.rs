@@ -0,0 +1,202 @@
+//! Checks the exact output of the diagnostic renderer, the source is compiled as `test.maga` inside a
+//! temporary directory so the paths in the output do not depend on the machine.
+
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn compile(name: &str, args: &[&str], source: &str) -> Output {
+    let dir =
+        std::env::temp_dir().join(format!("maga-diagnostics-{}-{}", std::process::id(), name));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("test.maga"), source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_maga"))
+        .args(args)
+        .args(["-o", "-", "test.maga"])
+        .current_dir(&dir)
+        .output()
+        .expect("Failed to run maga");
+    let _ = fs::remove_dir_all(&dir);
+
+    output
+}
+
+fn stderr(name: &str, args: &[&str], source: &str) -> String {
+    String::from_utf8(compile(name, args, source).stderr).unwrap()
+}
+
+#[test]
+fn parse_error() {
+    assert_eq!(
+        stderr(
+            "parse",
+            &["--color=never"],
+            "fun main(): i32 {\n    return 1\n}\n"
+        ),
+        "\
+error: Expected Token::Semicolon, found RBrace
+ --> test.maga:3:1
+  |
+3 | }
+  | ^
+"
+    );
+}
+
+#[test]
+fn label_is_aligned_with_the_column() {
+    assert_eq!(
+        stderr(
+            "variable",
+            &["--color=never"],
+            "fun main(): i32 {\n    let a = 1;\n    return a + b;\n}\n"
+        ),
+        "\
+error: Unknown variable: b
+ --> test.maga:3:16
+  |
+3 |     return a + b;
+  |                ^ not found in this scope
+"
+    );
+}
+
+#[test]
+fn gutter_grows_with_the_line_number() {
+    let source = format!("{}fun main(): i32 {{\n    return x;\n}}\n", "\n".repeat(9));
+    assert_eq!(
+        stderr("gutter", &["--color=never"], &source),
+        "\
+error: Unknown variable: x
+  --> test.maga:11:12
+   |
+11 |     return x;
+   |            ^ not found in this scope
+"
+    );
+}
+
+#[test]
+fn multi_line_label_is_underlined_to_the_end_of_its_first_line() {
+    let output = compile(
+        "unreachable",
+        &["--color=never"],
+        "fun main(): i32 {\n    return 1;\n    let a = 2;\n    let b = 3;\n}\n",
+    );
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "\
+warning: Unreachable statement
+ --> test.maga:3:5
+  |
+2 |     return 1;
+  |     --------- any code following this is never executed
+3 |     let a = 2;
+  |     ^^^^^^^^^^
+"
+    );
+}
+
+#[test]
+fn notes() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_maga"))
+        .args(["run", "--color=never", "-"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"fun main(a: i32, b: i32, c: i32): i32 {\n    return 1;\n}\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "\
+error: Invalid signature for `main`
+ --> -:1:10
+  |
+1 | fun main(a: i32, b: i32, c: i32): i32 {
+  |          ^^^^^^^^^^^^^^^^^^^^^^
+  = note: `main` must take either no parameters or `(argc: <integer>, argv: <pointer>)` and return an integer or nothing
+"
+    );
+}
+
+#[test]
+fn colors() {
+    let source = "fun main(): i32 {\n    return x;\n}\n";
+
+    assert_eq!(
+        stderr("always", &["--color=always"], source),
+        "\
+\x1b[1;31merror\x1b[0m\x1b[1m: Unknown variable: x\x1b[0m
+ \x1b[1;34m-->\x1b[0m test.maga:2:12
+  \x1b[1;34m|\x1b[0m
+\x1b[1;34m2\x1b[0m \x1b[1;34m|\x1b[0m     return x;
+  \x1b[1;34m|\x1b[0m            \x1b[1;31m^ not found in this scope\x1b[0m
+"
+    );
+    assert!(!stderr("never", &["--color=never"], source).contains('\x1b'));
+}
+
+#[test]
+fn json() {
+    let stderr = stderr(
+        "json",
+        &["--error-format=json"],
+        "fun main(): i32 {\n    let a = 1;\n    return a + b;\n}\n",
+    );
+
+    assert_eq!(
+        stderr.lines().count(),
+        1,
+        "one diagnostic per line:\n{}",
+        stderr
+    );
+    let diagnostic: Value = serde_json::from_str(&stderr).unwrap();
+
+    assert_eq!(diagnostic["severity"], "error");
+    assert_eq!(diagnostic["message"], "Unknown variable: b");
+    assert_eq!(diagnostic["file"], "test.maga");
+    assert_eq!(diagnostic["notes"], Value::Array(Vec::new()));
+
+    let label = &diagnostic["labels"][0];
+    assert_eq!(label["message"], "not found in this scope");
+    assert_eq!(label["primary"], true);
+    assert_eq!(
+        (label["start"].as_u64(), label["end"].as_u64()),
+        (Some(48), Some(49))
+    );
+    assert_eq!(
+        (label["line"].as_u64(), label["column"].as_u64()),
+        (Some(3), Some(16))
+    );
+}
+
+#[test]
+fn json_escapes_strings() {
+    //The file name ends up in the output, quotes, backslashes and control characters have to be escaped
+    let dir = std::env::temp_dir().join(format!("maga-diagnostics-{}-escape", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let name = "a \"quoted\"\\name\t.maga";
+    fs::write(dir.join(name), "fun main(): i32 {\n    return x;\n}\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_maga"))
+        .args(["--error-format=json", "-o", "-", name])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    let _ = fs::remove_dir_all(&dir);
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let diagnostic: Value = serde_json::from_str(&stderr).unwrap();
+    assert_eq!(diagnostic["file"], name);
+}
@@ -0,0 +1,58 @@
+//! Checks through `--emit=doc` that a block of `///` comments is attached to the function following it.
+
+mod common;
+
+use common::maga;
+
+fn doc(source: &str) -> String {
+    let output = maga(&["--emit=doc", "--color=never", "-o", "-"], source);
+
+    assert!(
+        output.status.success(),
+        "Failed to parse:\n{}\n{}",
+        source,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn attaches_to_following_function() {
+    let doc = doc(
+        "/// Adds one\n/// to its argument\nfun f(a: i32): i32 {\n    return a + 1;\n}\n\n// Not a doc comment\nfun g() {\n}\n\n/// Documented\n///\n/// too\nfun h() {\n}\n",
+    );
+
+    assert_eq!(
+        doc,
+        "fun f(a: i32): i32\n    Adds one\n    to its argument\n\nfun g()\n\nfun h()\n    Documented\n\n    too\n\n"
+    );
+}
+
+#[test]
+fn attaches_to_extern_function() {
+    let doc = doc(
+        "/// Writes formatted output\nextern \"C\" fun printf(fmt: *u8, ...): i32;\n\nfun main() {\n}\n",
+    );
+
+    assert_eq!(
+        doc,
+        "extern \"C\" fun printf(fmt: *u8, ...): i32\n    Writes formatted output\n\nfun main()\n\n"
+    );
+}
+
+#[test]
+fn requires_a_following_function() {
+    let output = maga(
+        &["--emit=doc", "--color=never", "-o", "-"],
+        "fun f() {\n}\n\n/// Dangling\n",
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("Doc comment is not followed by a function"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,92 @@
+//! Checks that `extern "C"` functions are declared for the linker and that maga programs can call into libc,
+//! including variadic functions like `printf`.
+
+mod common;
+
+use common::maga;
+
+fn assert_error(source: &str, message: &str) {
+    let output = maga(&["--emit=llvm-ir", "--color=never", "-o", "-"], source);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(
+        !output.status.success(),
+        "Expected an error for:\n{}",
+        source
+    );
+    assert!(
+        stderr.contains(message),
+        "Expected `{}` for:\n{}\ngot:\n{}",
+        message,
+        source,
+        stderr
+    );
+}
+
+#[test]
+fn declarations() {
+    let output = maga(
+        &["--emit=llvm-ir", "--color=never", "-o", "-"],
+        "extern \"C\" fun printf(fmt: *u8, ...): i32;\n\nfun main(): i32 {\n    let a = 1u8;\n    return printf(\"%d %d\\n\", 3, a);\n}\n",
+    );
+    let ir = String::from_utf8_lossy(&output.stdout);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    //Pointers are opaque since LLVM 15, so every pointer parameter prints as `ptr`
+    assert!(ir.contains("declare i32 @printf(ptr, ...)"), "{}", ir);
+    //Variadic arguments follow the default argument promotions of C
+    assert!(ir.contains("zext i8"), "{}", ir);
+    assert!(ir.contains("i32 3,"), "{}", ir);
+}
+
+#[test]
+fn variadic_floats_are_promoted() {
+    let source = "extern \"C\" fun printf(fmt: *u8, ...): i32;\n\nfun main(): i32 {\n    let half = 0.5f32;\n    printf(\"%.2f\\n\", half);\n    return 0;\n}\n";
+
+    let output = maga(&["--emit=llvm-ir", "--color=never", "-o", "-"], source);
+    let ir = String::from_utf8_lossy(&output.stdout);
+    assert!(ir.contains("fpext float"), "{}", ir);
+
+    //`printf` reads a `double`, an `f32` passed as is would print garbage
+    let output = maga(&["run"], source);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "0.50\n");
+}
+
+#[test]
+fn calls_into_libc() {
+    let output = maga(
+        &["run"],
+        "extern \"C\" fun printf(fmt: *u8, ...): i32;\nextern \"C\" fun abs(n: i32): i32;\n\nfun main(): i32 {\n    let written = printf(\"%s %d\\n\", \"maga\", 42);\n    return abs(-written);\n}\n",
+    );
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "maga 42\n");
+    assert_eq!(output.status.code(), Some(8));
+}
+
+#[test]
+fn invalid_declarations() {
+    assert_error(
+        "fun f(a: i32, ...) {\n}\n",
+        "Only extern functions can take a variable number of arguments",
+    );
+    assert_error(
+        "extern \"C\" fun f() {\n}\n",
+        "Extern function cannot have a body",
+    );
+    assert_error(
+        "extern \"Rust\" fun f();\n",
+        "Unsupported ABI \"Rust\", only \"C\" is supported",
+    );
+    assert_error(
+        "fun f(): i32;\n",
+        "Function without a body has to be declared with `extern \"C\"`",
+    );
+    assert_error(
+        "extern \"C\" fun printf(fmt: *u8, ...): i32;\n\nfun f(): i32 {\n    return printf();\n}\n",
+        "Function `printf` takes at least 1 argument but 0 were supplied",
+    );
+}
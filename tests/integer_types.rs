@@ -0,0 +1,111 @@
+//! Checks that every integer type picks the LLVM instruction and comparison predicate matching its
+//! signedness.
+
+use std::fs;
+use std::process::Command;
+
+const TYPES: [(&str, &str, bool); 8] = [
+    ("i8", "i8", true),
+    ("i16", "i16", true),
+    ("i32", "i32", true),
+    ("i64", "i64", true),
+    ("u8", "i8", false),
+    ("u16", "i16", false),
+    ("u32", "i32", false),
+    ("u64", "i64", false),
+];
+
+/// LLVM IR generated for a function applying `operator` to two parameters of type `r#type`
+fn ir(r#type: &str, operator: &str, return_type: &str) -> String {
+    let path = std::env::temp_dir().join(format!(
+        "maga-integer-{}-{}-{}.maga",
+        std::process::id(),
+        r#type,
+        operator
+            .bytes()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>()
+    ));
+    fs::write(
+        &path,
+        format!(
+            "fun f(a: {type}, b: {type}): {return_type} {{\n    return a {operator} b;\n}}\n",
+            r#type = r#type,
+            return_type = return_type,
+            operator = operator,
+        ),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_maga"))
+        .args(["--emit=llvm-ir", "--color=never", "-o", "-"])
+        .arg(&path)
+        .output()
+        .expect("Failed to run maga");
+    let _ = fs::remove_file(&path);
+
+    assert!(
+        output.status.success(),
+        "Failed to compile `a {} b` for {}:\n{}",
+        operator,
+        r#type,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn assert_instruction(operator: &str, signed: &str, unsigned: &str, returns_bool: bool) {
+    for (r#type, llvm_type, is_signed) in TYPES {
+        let return_type = if returns_bool { "bool" } else { r#type };
+        let instruction = if is_signed { signed } else { unsigned };
+        let expected = format!("= {} {} %", instruction, llvm_type);
+
+        let ir = ir(r#type, operator, return_type);
+        assert!(
+            ir.contains(&expected),
+            "`a {} b` for {} should contain `{}`:\n{}",
+            operator,
+            r#type,
+            expected,
+            ir
+        );
+    }
+}
+
+#[test]
+fn division() {
+    assert_instruction("/", "sdiv", "udiv", false);
+}
+
+#[test]
+fn remainder() {
+    assert_instruction("%", "srem", "urem", false);
+}
+
+#[test]
+fn shift_right() {
+    assert_instruction(">>", "ashr", "lshr", false);
+}
+
+#[test]
+fn sign_independent_arithmetic() {
+    assert_instruction("+", "add", "add", false);
+    assert_instruction("-", "sub", "sub", false);
+    assert_instruction("*", "mul", "mul", false);
+    assert_instruction("<<", "shl", "shl", false);
+}
+
+#[test]
+fn ordering_comparisons() {
+    assert_instruction("<", "icmp slt", "icmp ult", true);
+    assert_instruction("<=", "icmp sle", "icmp ule", true);
+    assert_instruction(">", "icmp sgt", "icmp ugt", true);
+    assert_instruction(">=", "icmp sge", "icmp uge", true);
+}
+
+#[test]
+fn equality_comparisons() {
+    assert_instruction("==", "icmp eq", "icmp eq", true);
+    assert_instruction("!=", "icmp ne", "icmp ne", true);
+}
@@ -0,0 +1,170 @@
+//! Runs programs through `maga run -`, the source is piped over stdin so nothing touches the filesystem.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(source: &str, args: &[&str]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_maga"))
+        .args(["run", "-"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run maga");
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+fn exit_code(source: &str, args: &[&str]) -> i32 {
+    let output = run(source, args);
+    output.status.code().unwrap_or_else(|| {
+        panic!(
+            "maga was terminated by a signal:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+    })
+}
+
+#[test]
+fn returns_exit_code() {
+    assert_eq!(
+        exit_code("fun main(): i32 {\n    return 4 * 3 + 6;\n}\n", &[]),
+        18
+    );
+    assert_eq!(exit_code("fun main(): i64 {\n    return 7;\n}\n", &[]), 7);
+    assert_eq!(exit_code("fun main() {\n    let x = 1;\n}\n", &[]), 0);
+}
+
+#[test]
+fn exit_code_keeps_low_byte() {
+    assert_eq!(
+        exit_code("fun main(): i32 {\n    return 300;\n}\n", &[]),
+        44
+    );
+    assert_eq!(
+        exit_code("fun main(): i32 {\n    return -1;\n}\n", &[]),
+        255
+    );
+}
+
+#[test]
+fn passes_arguments() {
+    let source = "fun main(argc: i32, argv: *u8): i32 {\n    return argc;\n}\n";
+
+    //The program name counts as the first argument, like in C
+    assert_eq!(exit_code(source, &[]), 1);
+    assert_eq!(exit_code(source, &["a", "b", "c"]), 4);
+}
+
+#[test]
+fn writes_to_stdout() {
+    let output = run(
+        "extern \"C\" fun puts(s: *u8): i32;\n\nfun main(): i32 {\n    puts(\"hello\");\n    return 0;\n}\n",
+        &[],
+    );
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn missing_main() {
+    let output = run("fun f(): i32 {\n    return 1;\n}\n", &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(125));
+    assert!(stderr.contains("main"), "unexpected output:\n{}", stderr);
+}
+
+#[test]
+fn compile_errors_use_distinct_exit_code() {
+    //A program returning 1 must not look like a program that failed to compile
+    assert_eq!(exit_code("fun main(): i32 {\n    return 1;\n}\n", &[]), 1);
+    assert_eq!(exit_code("fun main(): i32 {\n    return x;\n}\n", &[]), 125);
+}
+
+#[test]
+fn prefix_operators() {
+    assert_eq!(
+        exit_code(
+            "fun main(): i64 {\n    let a = 7;\n    return -a + 10;\n}\n",
+            &[]
+        ),
+        3
+    );
+    assert_eq!(
+        exit_code(
+            "fun main(): i64 {\n    let a = 5;\n    return ~a & 7;\n}\n",
+            &[]
+        ),
+        2
+    );
+    assert_eq!(
+        exit_code(
+            "fun main(): i64 {\n    let a = 9;\n    let p = &a;\n    return *p;\n}\n",
+            &[]
+        ),
+        9
+    );
+}
+
+#[test]
+fn negating_unsigned_is_rejected() {
+    let output = run(
+        "fun f(a: u32): u32 {\n    return -a;\n}\n\nfun main() {\n}\n",
+        &[],
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(125));
+    assert!(
+        stderr.contains("Operator `-` cannot be applied to type `u32`"),
+        "unexpected output:\n{}",
+        stderr
+    );
+}
+
+#[test]
+fn logical_operators_short_circuit() {
+    //`set` would store 1 in `hit` if the right-hand side was evaluated
+    let set = "fun set(p: *var i32): bool {\n    *p = 1;\n    return 1 == 1;\n}\n\n";
+
+    assert_eq!(
+        exit_code(
+            &format!(
+                "{}fun main(): i32 {{\n    var hit = 0i32;\n    let x = 1 == 2 && set(&hit);\n    return hit;\n}}\n",
+                set
+            ),
+            &[]
+        ),
+        0
+    );
+    assert_eq!(
+        exit_code(
+            &format!(
+                "{}fun main(): i32 {{\n    var hit = 0i32;\n    let x = 1 == 1 || set(&hit);\n    return hit;\n}}\n",
+                set
+            ),
+            &[]
+        ),
+        0
+    );
+    assert_eq!(
+        exit_code(
+            &format!(
+                "{}fun main(): i32 {{\n    var hit = 0i32;\n    let x = 1 == 1 && set(&hit);\n    return hit;\n}}\n",
+                set
+            ),
+            &[]
+        ),
+        1
+    );
+}
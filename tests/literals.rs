@@ -0,0 +1,95 @@
+//! Checks the values and types of integer literals by running programs returning them.
+
+mod common;
+
+use common::maga;
+use std::process::Output;
+
+fn run(source: &str) -> Output {
+    maga(&["run", "--color=never"], source)
+}
+
+fn exit_code(source: &str) -> i32 {
+    let output = run(source);
+    assert!(
+        output.status.code().is_some_and(|code| code != 125),
+        "Failed to compile:\n{}\n{}",
+        source,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.status.code().unwrap()
+}
+
+fn assert_error(source: &str, message: &str) {
+    let output = run(source);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(125), "{}", source);
+    assert!(stderr.contains(message), "unexpected output:\n{}", stderr);
+}
+
+#[test]
+fn bases() {
+    assert_eq!(exit_code("fun main(): i32 {\n    return 0x1f;\n}\n"), 31);
+    assert_eq!(exit_code("fun main(): i32 {\n    return 0o17;\n}\n"), 15);
+    assert_eq!(exit_code("fun main(): i32 {\n    return 0b101;\n}\n"), 5);
+}
+
+#[test]
+fn digit_separators() {
+    assert_eq!(exit_code("fun main(): i32 {\n    return 1_0;\n}\n"), 10);
+    assert_eq!(
+        exit_code("fun main(): u8 {\n    return 0b1111_0000u8 + 0x0_fu8;\n}\n"),
+        255
+    );
+    assert_eq!(
+        exit_code("fun main(): i64 {\n    return 1_000 - 900;\n}\n"),
+        100
+    );
+}
+
+#[test]
+fn suffix_range() {
+    assert_eq!(exit_code("fun main(): u8 {\n    return 255u8;\n}\n"), 255);
+    assert_eq!(exit_code("fun main(): i8 {\n    return -128i8;\n}\n"), 128);
+    assert_error(
+        "fun main(): u8 {\n    return 256u8;\n}\n",
+        "Literal `256` does not fit into `u8`",
+    );
+    assert_error(
+        "fun main(): i8 {\n    return -129i8;\n}\n",
+        "Literal `-129` does not fit into `i8`",
+    );
+    assert_error(
+        "fun main(): u8 {\n    return 0x100;\n}\n",
+        "Literal `256` does not fit into `u8`",
+    );
+}
+
+#[test]
+fn untyped_local_takes_type_of_first_use() {
+    //`b` is an `i32` because it is returned from a function returning `i32`
+    assert_eq!(
+        exit_code("fun main(): i32 {\n    let b = 4 * 3 + 6;\n    return b;\n}\n"),
+        18
+    );
+    assert_eq!(
+        exit_code(
+            "fun main(argc: i32, argv: *u8): i32 {\n    let b = 2;\n    let c = b * 3;\n    return argc + c;\n}\n"
+        ),
+        7
+    );
+    assert_error(
+        "fun main(): u8 {\n    let b = 300;\n    return b;\n}\n",
+        "Literal `300` does not fit into `u8`",
+    );
+}
+
+#[test]
+fn untyped_local_has_a_single_type() {
+    assert_error(
+        "fun main(argc: i32, argv: *u8): i64 {\n    let b = 2;\n    let c = argc + b;\n    return b;\n}\n",
+        "Types are not equal: `i32` and `i64`",
+    );
+}
@@ -0,0 +1,152 @@
+//! Checks the instructions generated for expressions through `--emit=llvm-ir`.
+
+mod common;
+
+use common::maga;
+
+fn ir(source: &str) -> String {
+    let output = maga(&["--emit=llvm-ir", "--color=never", "-o", "-"], source);
+
+    assert!(
+        output.status.success(),
+        "Failed to compile:\n{}\n{}",
+        source,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Trimmed lines of `ir` that contain `pattern`
+fn lines<'a>(ir: &'a str, pattern: &str) -> Vec<&'a str> {
+    ir.lines()
+        .map(str::trim)
+        .filter(|line| line.contains(pattern))
+        .collect()
+}
+
+/// Trimmed instructions of the basic block called `label`
+fn block<'a>(ir: &'a str, label: &str) -> Vec<&'a str> {
+    ir.lines()
+        .skip_while(|line| !line.starts_with(&format!("{}:", label)))
+        .skip(1)
+        .take_while(|line| !line.is_empty() && !line.starts_with('}'))
+        .map(str::trim)
+        .collect()
+}
+
+#[test]
+fn logical_and_short_circuits() {
+    let ir = ir("fun f(a: i64, b: i64) {\n    let x = a == b && b != 1;\n}\n");
+
+    //The right hand side has its own block that is skipped when the left hand side is false
+    let branches = lines(&ir, "br ");
+    assert_eq!(branches.len(), 2, "{}", ir);
+    assert!(
+        branches[0].ends_with("label %logic_rhs, label %logic_merge"),
+        "{}",
+        ir
+    );
+    assert_eq!(branches[1], "br label %logic_merge");
+
+    let rhs = block(&ir, "logic_rhs");
+    assert!(rhs.iter().any(|line| line.contains("icmp ne")), "{}", ir);
+
+    let phi = lines(&ir, "phi");
+    assert_eq!(phi.len(), 1, "{}", ir);
+    assert!(
+        phi[0].starts_with("%logic_temp = phi i1 [ false, %entry ], [ %")
+            && phi[0].ends_with(", %logic_rhs ]"),
+        "{}",
+        ir
+    );
+}
+
+#[test]
+fn logical_or_short_circuits() {
+    let ir = ir("fun f(a: i64, b: i64) {\n    let x = a == b || b != 1;\n}\n");
+
+    let branches = lines(&ir, "br ");
+    assert_eq!(branches.len(), 2, "{}", ir);
+    assert!(
+        branches[0].ends_with("label %logic_merge, label %logic_rhs"),
+        "{}",
+        ir
+    );
+    assert_eq!(branches[1], "br label %logic_merge");
+
+    let rhs = block(&ir, "logic_rhs");
+    assert!(rhs.iter().any(|line| line.contains("icmp ne")), "{}", ir);
+
+    let phi = lines(&ir, "phi");
+    assert_eq!(phi.len(), 1, "{}", ir);
+    assert!(
+        phi[0].starts_with("%logic_temp = phi i1 [ true, %entry ], [ %")
+            && phi[0].ends_with(", %logic_rhs ]"),
+        "{}",
+        ir
+    );
+}
+
+#[test]
+fn nested_logical_operators_branch_from_the_last_block() {
+    let ir = ir("fun f(a: i64, b: i64) {\n    let x = a == 1 && b == 2 || a == b;\n}\n");
+
+    //The inner `&&` ends in its merge block, the `||` has to branch from there and not from `entry`
+    assert_eq!(lines(&ir, "phi").len(), 2, "{}", ir);
+    assert!(
+        ir.contains("[ true, %logic_merge ]"),
+        "the outer phi must name the block the inner one ended in:\n{}",
+        ir
+    );
+}
+
+#[test]
+fn float_arithmetic() {
+    let ir = ir(
+        "fun f(a: f64, b: f64): f64 {\n    return (a + b) * (a - b) / (a % b);\n}\n\nfun g(a: f32): f32 {\n    return a + 1.5f32;\n}\n",
+    );
+
+    for instruction in [
+        "fadd double",
+        "fsub double",
+        "fmul double",
+        "fdiv double",
+        "frem double",
+        "fadd float",
+    ] {
+        assert_eq!(lines(&ir, instruction).len(), 1, "{}:\n{}", instruction, ir);
+    }
+    assert!(lines(&ir, "add i").is_empty(), "{}", ir);
+}
+
+#[test]
+fn float_comparisons_are_ordered() {
+    for (operator, predicate) in [
+        ("==", "oeq"),
+        ("!=", "one"),
+        ("<", "olt"),
+        ("<=", "ole"),
+        (">", "ogt"),
+        (">=", "oge"),
+    ] {
+        let ir = ir(&format!(
+            "fun f(a: f64, b: f64) {{\n    let x = a {} b;\n}}\n",
+            operator
+        ));
+
+        assert_eq!(
+            lines(&ir, "fcmp"),
+            [format!("%cmp_temp = fcmp {} double %a1, %b2", predicate)],
+            "{}",
+            ir
+        );
+    }
+}
+
+#[test]
+fn float_negation() {
+    let ir = ir("fun f(a: f64): f64 {\n    return -a;\n}\n");
+
+    assert_eq!(lines(&ir, "fneg double").len(), 1, "{}", ir);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::maga;
+use std::fs;
+use std::process::Command;
+
+const SOURCE: &str = "fun main(argc: i32, argv: *u8): i32 { return 7; }";
+
+#[test]
+fn object_file() {
+    let output = maga(&["--emit=obj", "-o", "-"], SOURCE);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.stdout.starts_with(b"\x7fELF"), "not an ELF object");
+}
+
+#[test]
+fn assembly() {
+    let output = maga(&["--emit=asm", "-o", "-"], SOURCE);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let asm = String::from_utf8(output.stdout).unwrap();
+    assert!(asm.contains("main:"), "{}", asm);
+}
+
+#[test]
+fn executable_from_example() {
+    let executable = std::env::temp_dir().join(format!("maga-example-{}", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_maga"))
+        .args(["--emit=exe", "-o"])
+        .arg(&executable)
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/files/example.maga"))
+        .output()
+        .expect("Failed to run maga");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let status = Command::new(&executable)
+        .status()
+        .expect("Failed to run the executable");
+    let _ = fs::remove_file(&executable);
+    assert_eq!(status.code(), Some(18));
+}
+
+#[test]
+fn executable_to_stdout_is_rejected() {
+    let output = maga(&["--emit=exe", "-o", "-"], SOURCE);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("Cannot write an executable to stdout")
+    );
+}
@@ -0,0 +1,147 @@
+//! Pins the trees produced for binary operators by comparing the AST of an expression with the AST of the
+//! same expression with explicit parentheses, parentheses don't show up in the AST besides the spans.
+
+mod common;
+
+use common::maga;
+
+/// AST of `expression` with all spans removed
+fn ast(expression: &str) -> String {
+    let output = maga(
+        &["--emit=ast", "--color=never", "-o", "-"],
+        &format!(
+            "fun f(a: i64, b: i64, c: i64, d: i64) {{\n    let x = {};\n}}\n",
+            expression
+        ),
+    );
+
+    assert!(
+        output.status.success(),
+        "Failed to parse `{}`:\n{}",
+        expression,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    strip_spans(&String::from_utf8(output.stdout).unwrap())
+}
+
+fn strip_spans(ast: &str) -> String {
+    let mut stripped = String::new();
+    let mut in_span = false;
+
+    for line in ast.lines() {
+        let trimmed = line.trim();
+
+        if in_span {
+            in_span = trimmed != "},";
+        } else if trimmed.ends_with("span: Span {") {
+            in_span = true;
+        } else {
+            stripped.push_str(line);
+            stripped.push('\n');
+        }
+    }
+
+    stripped
+}
+
+fn assert_parses_as(expression: &str, grouped: &str) {
+    assert_eq!(
+        ast(expression),
+        ast(grouped),
+        "`{}` should parse as `{}`",
+        expression,
+        grouped
+    );
+}
+
+#[test]
+fn parentheses_change_the_tree() {
+    assert_ne!(ast("a - b - c"), ast("a - (b - c)"));
+}
+
+#[test]
+fn arithmetic_is_left_associative() {
+    assert_parses_as("a - b - c", "(a - b) - c");
+    assert_parses_as("a + b - c + d", "((a + b) - c) + d");
+    assert_parses_as("a / b / c", "(a / b) / c");
+    assert_parses_as("a % b * c / d", "((a % b) * c) / d");
+}
+
+#[test]
+fn multiplication_binds_tighter_than_addition() {
+    assert_parses_as("a + b * c", "a + (b * c)");
+    assert_parses_as("a * b + c", "(a * b) + c");
+    assert_parses_as("a - b / c - d", "(a - (b / c)) - d");
+    assert_parses_as("a + b % c", "a + (b % c)");
+}
+
+#[test]
+fn addition_and_subtraction_share_a_tier() {
+    assert_parses_as("a - b + c", "(a - b) + c");
+    assert_parses_as("a + b - c", "(a + b) - c");
+}
+
+#[test]
+fn comparisons_bind_looser_than_arithmetic() {
+    assert_parses_as("a + b < c * d", "(a + b) < (c * d)");
+    assert_parses_as("a <= b - c", "a <= (b - c)");
+    assert_parses_as("a * b > c", "(a * b) > c");
+    assert_parses_as("a >= b / c + d", "a >= ((b / c) + d)");
+}
+
+#[test]
+fn comparisons_are_left_associative() {
+    assert_parses_as("a < b < c", "(a < b) < c");
+    assert_parses_as("a > b <= c", "(a > b) <= c");
+}
+
+#[test]
+fn tighter_operator_after_looser_operator() {
+    assert_parses_as("a < b + c * d - a", "a < ((b + (c * d)) - a)");
+}
+
+#[test]
+fn shifts_bind_looser_than_arithmetic() {
+    assert_parses_as("a << b + c", "a << (b + c)");
+    assert_parses_as("a * b >> c", "(a * b) >> c");
+    assert_parses_as("a << b >> c", "(a << b) >> c");
+}
+
+#[test]
+fn bitwise_operators_have_separate_tiers() {
+    assert_parses_as("a | b ^ c & d", "a | (b ^ (c & d))");
+    assert_parses_as("a & b | c", "(a & b) | c");
+    assert_parses_as("a & b << c", "a & (b << c)");
+}
+
+#[test]
+fn bitwise_operators_bind_tighter_than_comparisons() {
+    assert_parses_as("a & b == c", "(a & b) == c");
+    assert_parses_as("a < b | c", "a < (b | c)");
+}
+
+#[test]
+fn equality_binds_looser_than_comparisons() {
+    assert_parses_as("a < b == c > d", "(a < b) == (c > d)");
+    assert_parses_as("a == b != c", "(a == b) != c");
+}
+
+#[test]
+fn logical_and_binds_tighter_than_logical_or() {
+    assert_parses_as("a || b && c", "a || (b && c)");
+    assert_parses_as("a && b || c && d", "(a && b) || (c && d)");
+    assert_parses_as("a == b && c != d", "(a == b) && (c != d)");
+    assert_parses_as("a || b || c", "(a || b) || c");
+}
+
+#[test]
+fn prefix_operators_bind_tighter_than_binary_operators() {
+    assert_parses_as("-a * b", "(-a) * b");
+    assert_parses_as("a - -b", "a - (-b)");
+    assert_parses_as("a -1", "a - 1");
+    assert_parses_as("!a && b", "(!a) && b");
+    assert_parses_as("~a & b", "(~a) & b");
+    assert_parses_as("*a + *b", "(*a) + (*b)");
+    assert_parses_as("-*&a", "-(*(&a))");
+}
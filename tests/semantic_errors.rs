@@ -0,0 +1,347 @@
+//! Checks that semantic analysis rejects ill-formed programs with a diagnostic instead of reaching code
+//! generation.
+
+mod common;
+
+use common::maga;
+
+/// Compiles `source` to LLVM IR, returns whether it succeeded and what was written to stderr
+fn compile(source: &str) -> (bool, String) {
+    let output = maga(&["--emit=llvm-ir", "--color=never", "-o", "-"], source);
+
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+fn assert_error(source: &str, message: &str) {
+    let (success, stderr) = compile(source);
+
+    assert!(!success, "Expected an error for:\n{}", source);
+    assert!(
+        stderr.contains(message),
+        "Expected `{}` for:\n{}\ngot:\n{}",
+        message,
+        source,
+        stderr
+    );
+}
+
+fn assert_ok(source: &str) {
+    let (success, stderr) = compile(source);
+
+    assert!(success, "Failed to compile:\n{}\n{}", source, stderr);
+}
+
+#[test]
+fn unknown_names() {
+    assert_error("fun f(): i32 {\n    return x;\n}\n", "Unknown variable: x");
+    assert_error(
+        "fun f(a: i33): i32 {\n    return 0;\n}\n",
+        "Unknown type: i33",
+    );
+}
+
+#[test]
+fn duplicate_functions() {
+    assert_error(
+        "fun f() {\n}\n\nfun f() {\n}\n",
+        "Function `f` is defined multiple times",
+    );
+}
+
+#[test]
+fn return_types() {
+    assert_error(
+        "fun f(a: i64): i32 {\n    return a;\n}\n",
+        "Types are not equal: `i64` and `i32`",
+    );
+    assert_error(
+        "fun f(): i32 {\n    let a = 1;\n}\n",
+        "Function f does not return a value on every path",
+    );
+    assert_error(
+        "fun f(): i32 {\n    return;\n}\n",
+        "Function f does not return a value on every path",
+    );
+    assert_error(
+        "fun f() {\n    return 1;\n}\n",
+        "the function has no return type",
+    );
+}
+
+#[test]
+fn errors_in_every_function_are_reported() {
+    let (success, stderr) =
+        compile("fun f(): i32 {\n    return x;\n}\n\nfun g(): i32 {\n    return y;\n}\n");
+
+    assert!(!success);
+    assert!(stderr.contains("Unknown variable: x"), "{}", stderr);
+    assert!(stderr.contains("Unknown variable: y"), "{}", stderr);
+}
+
+#[test]
+fn errors_in_every_statement_are_reported() {
+    let (success, stderr) =
+        compile("fun f(a: i32): i32 {\n    let b: i64 = a;\n    let c = *a;\n    return x;\n}\n");
+
+    assert!(!success);
+    assert_eq!(stderr.matches("error:").count(), 3, "{}", stderr);
+    assert!(
+        stderr.contains("Types are not equal: `i32` and `i64`"),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("Operator `*` cannot be applied to type `i32`"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("Unknown variable: x"), "{}", stderr);
+}
+
+#[test]
+fn failed_declarations_are_reported_once() {
+    let (success, stderr) =
+        compile("fun f(): i32 {\n    let a = x;\n    let b = a + 1;\n    return b;\n}\n");
+
+    assert!(!success);
+    assert_eq!(stderr.matches("error:").count(), 1, "{}", stderr);
+    assert!(stderr.contains("Unknown variable: x"), "{}", stderr);
+
+    //The failed declaration refers to an earlier variable of the same name, which is still reported
+    let (_, stderr) = compile("fun f(): i32 {\n    let a = a;\n    return 0;\n}\n");
+    assert!(stderr.contains("Unknown variable: a"), "{}", stderr);
+}
+
+#[test]
+fn negation() {
+    assert_error(
+        "fun f(a: u32): u32 {\n    return -a;\n}\n",
+        "Operator `-` cannot be applied to type `u32`",
+    );
+    assert_error(
+        "fun f(a: u8): u8 {\n    return -(a + 1);\n}\n",
+        "Operator `-` cannot be applied to type `u8`",
+    );
+    assert_error(
+        "fun f(): u64 {\n    return -1;\n}\n",
+        "Literal `-1` does not fit into `u64`",
+    );
+}
+
+#[test]
+fn places() {
+    assert_ok("fun f(a: i32): i32 {\n    let p = &a;\n    return *p;\n}\n");
+    assert_ok("fun f(p: *i32): *i32 {\n    return &*p;\n}\n");
+    assert_error(
+        "fun f(a: i32): *i32 {\n    return &(a + 1);\n}\n",
+        "Cannot take the address of a temporary value",
+    );
+    assert_error(
+        "fun f(a: i32): i32 {\n    return *a;\n}\n",
+        "Operator `*` cannot be applied to type `i32`",
+    );
+}
+
+#[test]
+fn scopes() {
+    assert_ok("fun f(a: i32): i32 {\n    let a = a + 1;\n    {\n        let a = 2i64;\n    }\n    return a;\n}\n");
+    assert_error(
+        "fun f(): i32 {\n    {\n        let a = 1i32;\n    }\n    return a;\n}\n",
+        "Variable a is not in scope",
+    );
+    assert_error(
+        "fun f(): i32 {\n    let a = 1i32;\n    return a;\n}\n\nfun g(): i32 {\n    return a;\n}\n",
+        "Unknown variable: a",
+    );
+}
+
+#[test]
+fn failed_declarations_are_scoped_to_their_block() {
+    let (success, stderr) = compile(
+        "fun f(): i32 {\n    {\n        let a = x;\n        let b = a;\n    }\n    return a;\n}\n",
+    );
+
+    assert!(!success);
+    assert_eq!(stderr.matches("error:").count(), 2, "{}", stderr);
+    assert!(stderr.contains("Unknown variable: x"), "{}", stderr);
+    assert!(stderr.contains("Unknown variable: a"), "{}", stderr);
+}
+
+#[test]
+fn mutability() {
+    assert_ok("fun f(p: *var i32): i32 {\n    var a = 1i32;\n    a = 2;\n    a += 3;\n    *p = a;\n    return a;\n}\n");
+    assert_ok("fun f(): i32 {\n    var a = 1i32;\n    let p = &a;\n    *p = 2;\n    let q: *i32 = p;\n    return *q;\n}\n");
+    assert_error(
+        "fun f(): i32 {\n    let a = 1i32;\n    a = 2;\n    return a;\n}\n",
+        "cannot assign to a `let` binding",
+    );
+    assert_error(
+        "fun f(a: i32): i32 {\n    a += 2;\n    return a;\n}\n",
+        "cannot assign to a parameter",
+    );
+    assert_error(
+        "fun f(a: i32): i32 {\n    a + 1 = 2;\n    return a;\n}\n",
+        "Cannot assign to this expression",
+    );
+    assert_error(
+        "fun f(): f64 {\n    var a = 1.0;\n    a = 2i32;\n    return a;\n}\n",
+        "Types are not equal: `f64` and `i32`",
+    );
+}
+
+#[test]
+fn immutable_values_cannot_be_assigned_through_pointers() {
+    assert_error(
+        "fun f(): i32 {\n    let x = 1i32;\n    let p = &x;\n    *p = 2;\n    return x;\n}\n",
+        "Cannot assign through a pointer of type `*i32`",
+    );
+    assert_error(
+        "fun f(a: i32): i32 {\n    let p = &a;\n    *p += 1;\n    return a;\n}\n",
+        "Cannot assign through a pointer of type `*i32`",
+    );
+    assert_error(
+        "fun f(p: *i32) {\n    *p = 1;\n}\n",
+        "Cannot assign through a pointer of type `*i32`",
+    );
+    //A `*var` pointer can only be taken from a `var` binding
+    assert_error(
+        "fun f(): i32 {\n    let x = 1i32;\n    let p: *var i32 = &x;\n    return *p;\n}\n",
+        "Types are not equal: `*i32` and `*var i32`",
+    );
+}
+
+#[test]
+fn conditions() {
+    assert_ok("fun f(a: i32): i32 {\n    if a < 0 {\n        return 0;\n    } else if a == 0 {\n        return 1;\n    } else {\n        return 2;\n    }\n}\n");
+    assert_ok("fun f(a: i32): i32 {\n    var b = a;\n    if a < 0 {\n        b = -a;\n    }\n    return b;\n}\n");
+    assert_error(
+        "fun f(a: i32): i32 {\n    if a {\n        return 1;\n    }\n    return 0;\n}\n",
+        "Expected a condition of type `bool`, found `i32`",
+    );
+    assert_error(
+        "fun f(a: i32): i32 {\n    if a > 0 {\n        return 1;\n    }\n}\n",
+        "Function f does not return a value on every path",
+    );
+    assert_error(
+        "fun f(a: i32): i32 {\n    if a > 0 {\n        return 1;\n    } else if a < 0 {\n        return 2;\n    }\n}\n",
+        "Function f does not return a value on every path",
+    );
+}
+
+#[test]
+fn loops() {
+    assert_ok("fun f(): i32 {\n    loop {\n        return 1;\n    }\n}\n");
+    assert_ok("fun f(a: i32): i32 {\n    var i = 0i32;\n    while i < a {\n        i += 1;\n        if i == 3 {\n            continue;\n        }\n        if i == 5 {\n            break;\n        }\n    }\n    return i;\n}\n");
+    assert_error("fun f() {\n    break;\n}\n", "`break` outside of a loop");
+    assert_error(
+        "fun f() {\n    if 1 < 2 {\n        continue;\n    }\n}\n",
+        "`continue` outside of a loop",
+    );
+    assert_error(
+        "fun f(): i32 {\n    loop {\n        break;\n    }\n}\n",
+        "Function f does not return a value on every path",
+    );
+}
+
+#[test]
+fn unreachable_code() {
+    let (success, stderr) =
+        compile("fun f() {\n    loop {\n        break;\n        let b = 2;\n    }\n    return;\n    let a = 1;\n}\n");
+
+    assert!(success, "{}", stderr);
+    assert_eq!(
+        stderr.matches("warning: Unreachable statement").count(),
+        2,
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn labels() {
+    assert_ok("fun f(): i32 {\n    'outer: loop {\n        while 1 < 2 {\n            break 'outer;\n        }\n    }\n    return 0;\n}\n");
+    assert_ok("fun f() {\n    'outer: while 1 < 2 {\n        loop {\n            continue 'outer;\n        }\n    }\n}\n");
+    assert_error(
+        "fun f() {\n    loop {\n        break 'outer;\n    }\n}\n",
+        "Use of undeclared label `'outer`",
+    );
+    assert_error(
+        "fun f() {\n    'outer: loop {\n        break;\n    }\n    loop {\n        continue 'outer;\n    }\n}\n",
+        "Use of undeclared label `'outer`",
+    );
+}
+
+#[test]
+fn loop_values() {
+    assert_ok("fun f(a: i32): i32 {\n    let b = loop {\n        if a > 0 {\n            break a;\n        }\n        break 0;\n    };\n    return b;\n}\n");
+    assert_ok(
+        "fun f(): i32 {\n    let a: i32 = loop {\n        return 1;\n    };\n    return a;\n}\n",
+    );
+    assert_error(
+        "fun f() {\n    while 1 < 2 {\n        break 1;\n    }\n}\n",
+        "`break` with a value is only allowed inside `loop`",
+    );
+    assert_error(
+        "fun f() {\n    let a = loop {\n        if 1 < 2 {\n            break 1i32;\n        }\n        break 2i64;\n    };\n}\n",
+        "Types are not equal: `i32` and `i64`",
+    );
+    assert_error(
+        "fun f() {\n    let a = loop {\n        break;\n    };\n}\n",
+        "Variable a has type `()`",
+    );
+}
+
+#[test]
+fn calls() {
+    assert_ok("fun f(): i32 {\n    return g(1, 2);\n}\n\nfun g(a: i32, b: i64): i32 {\n    return a;\n}\n");
+    assert_error(
+        "fun f(): i32 {\n    return g(1);\n}\n",
+        "Unknown function: g",
+    );
+    assert_error(
+        "fun f(): i32 {\n    return g(1);\n}\n\nfun g(a: i32, b: i32): i32 {\n    return a;\n}\n",
+        "Function `g` takes 2 arguments but 1 was supplied",
+    );
+    assert_error(
+        "fun f(): i32 {\n    return g();\n}\n\nfun g(a: i32): i32 {\n    return a;\n}\n",
+        "Function `g` takes 1 argument but 0 were supplied",
+    );
+    assert_error(
+        "fun f(): i32 {\n    return g(1i64);\n}\n\nfun g(a: i32): i32 {\n    return a;\n}\n",
+        "Types are not equal: `i64` and `i32`",
+    );
+    assert_error(
+        "fun f() {\n    let a = g();\n}\n\nfun g() {\n}\n",
+        "Variable a has type `()`",
+    );
+}
+
+#[test]
+fn calls_to_functions_with_invalid_signatures_are_not_reported() {
+    let (success, stderr) =
+        compile("fun f(): i32 {\n    return g(1);\n}\n\nfun g(a: i33): i32 {\n    return 0;\n}\n");
+
+    assert!(!success);
+    assert_eq!(stderr.matches("error:").count(), 1, "{}", stderr);
+    assert!(stderr.contains("Unknown type: i33"), "{}", stderr);
+}
+
+#[test]
+fn unused_values() {
+    let (success, stderr) = compile(
+        "fun f() {\n    g();\n    h();\n}\n\nfun g() {\n}\n\nfun h(): i32 {\n    return 1;\n}\n",
+    );
+
+    assert!(success, "{}", stderr);
+    assert_eq!(
+        stderr
+            .matches("warning: Unused value of type `i32`")
+            .count(),
+        1,
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,88 @@
+//! Checks that the parser recovers from syntax errors and reports every one of them in a single run.
+
+mod common;
+
+use common::maga;
+
+/// Parses `source`, returns whether it succeeded and what was written to stderr
+fn parse(source: &str) -> (bool, String) {
+    let output = maga(&["--emit=ast", "--color=never", "-o", "-"], source);
+
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+fn assert_errors(source: &str, lines: &[usize]) {
+    let (success, stderr) = parse(source);
+
+    assert!(!success, "Expected errors for:\n{}", source);
+    assert_eq!(
+        stderr.matches("error:").count(),
+        lines.len(),
+        "Expected {} errors for:\n{}\ngot:\n{}",
+        lines.len(),
+        source,
+        stderr
+    );
+
+    for line in lines {
+        assert!(
+            stderr.contains(&format!(".maga:{}:", line)),
+            "Expected an error on line {} for:\n{}\ngot:\n{}",
+            line,
+            source,
+            stderr
+        );
+    }
+}
+
+#[test]
+fn errors_in_different_functions() {
+    assert_errors(
+        "fun f(): i32 {\n    let x = ;\n    return 1;\n}\n\nfun g() {\n    let y = 2 +;\n}\n",
+        &[2, 7],
+    );
+}
+
+#[test]
+fn errors_in_one_function() {
+    assert_errors(
+        "fun f(): i32 {\n    let x = ;\n    let z = 3 * ;\n    return 1;\n}\n",
+        &[2, 3],
+    );
+}
+
+#[test]
+fn error_in_signature_and_body() {
+    assert_errors(
+        "fun main( {\n    return 0;\n}\n\nfun g() {\n    let y = (1 + 2;\n}\n",
+        &[1, 6],
+    );
+}
+
+#[test]
+fn missing_brace_before_next_function() {
+    assert_errors(
+        "fun f(): i32 {\n    return 1;\n\nfun g() {\n    let x = ;\n}\n",
+        &[4, 5],
+    );
+}
+
+#[test]
+fn lex_and_parse_errors_are_both_reported() {
+    assert_errors(
+        "fun f(): i32 {\n    let a = 1 @ 2;\n    let b = ;\n    return 1;\n}\n",
+        &[2, 3],
+    );
+}
+
+#[test]
+fn missing_brace_of_nested_block() {
+    //The nested block takes the closing brace of the function, which is then missing before `fun g`
+    assert_errors(
+        "fun f(): i32 {\n    {\n        let a = 1;\n    return a;\n}\n\nfun g() {\n    let x = ;\n}\n",
+        &[7, 8],
+    );
+}
@@ -0,0 +1,122 @@
+//! Feeds truncated versions of the files in `tests/corpus` to the compiler front-end, a truncated file has to
+//! produce diagnostics and never crash the compiler.
+
+mod common;
+
+use common::maga;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn corpus() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+
+    let mut files = fs::read_dir(dir)
+        .expect("Failed to read corpus directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "maga")
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+
+    assert!(!files.is_empty(), "Corpus is empty");
+    files
+}
+
+/// Runs the front-end on `source` and returns a description of the problem if it crashed or failed without
+/// reporting an error, otherwise returns what was written to stderr
+fn front_end(source: &str) -> Result<String, String> {
+    let output = maga(&["--emit=ast", "--color=never", "-o", "-"], source);
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+    //Rust exits with 101 on panics, a signal leaves no exit code at all
+    if output.status.code().is_none_or(|code| code == 101) || stderr.contains("panicked") {
+        Err(stderr)
+    } else if !output.status.success() && !stderr.contains("error:") {
+        Err(format!("failed without reporting an error:\n{}", stderr))
+    } else {
+        Ok(stderr)
+    }
+}
+
+/// Prefixes of `source` cut in front of every token, plus the whole file. Every token ends one of them, so the
+/// parser runs out of input at every point it can, without running the compiler once per byte.
+fn prefixes(source: &str) -> impl Iterator<Item = &str> {
+    let is_word = |char: char| char.is_alphanumeric() || char == '_';
+
+    let mut ends = vec![source.len()];
+    let mut previous = None;
+
+    for (index, char) in source.char_indices() {
+        //Every symbol starts a token, e.g. `<=` can be cut off after `<`, words only start after something else
+        let continues_word = is_word(char) && previous.is_some_and(is_word);
+        if !char.is_whitespace() && !continues_word {
+            ends.push(index);
+        }
+
+        previous = Some(char);
+    }
+
+    ends.sort();
+    ends.dedup();
+    ends.into_iter().map(|end| &source[..end])
+}
+
+#[test]
+fn truncated_inputs_do_not_crash() {
+    let mut failures = Vec::new();
+
+    for file in corpus() {
+        let source = fs::read_to_string(&file).unwrap();
+
+        for prefix in prefixes(&source) {
+            if let Err(stderr) = front_end(prefix) {
+                failures.push(format!(
+                    "{} truncated at byte {}:\n{}",
+                    file.display(),
+                    prefix.len(),
+                    stderr
+                ));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn truncated_inputs_report_lex_errors() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/lex_errors.maga");
+    let source = fs::read_to_string(path).unwrap();
+
+    //Once a prefix contains the offending text, its lex error has to be reported no matter where it is cut off
+    let errors = [
+        ("12ab", "Malformed number `12ab`"),
+        ("@", "Unknown character `@`"),
+        (
+            "99999999999999999999 ",
+            "Integer literal `99999999999999999999` is too large",
+        ),
+        ("$", "Unknown character `$`"),
+        ("é", "Unknown character `é`"),
+    ];
+    let mut failures = Vec::new();
+
+    for prefix in prefixes(&source) {
+        let stderr = front_end(prefix).unwrap_or_else(|stderr| stderr);
+
+        for (text, message) in errors {
+            if prefix.contains(text) && !stderr.contains(message) {
+                failures.push(format!(
+                    "lex_errors.maga truncated at byte {} is missing `{}`:\n{}",
+                    prefix.len(),
+                    message,
+                    stderr
+                ));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}